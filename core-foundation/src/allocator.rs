@@ -8,16 +8,33 @@ pub type CFAllocatorReallocateCallBack = unsafe extern fn(ptr: *mut c_void, news
 pub type CFAllocatorDeallocateCallBack = unsafe extern fn(ptr: *mut c_void, info: *const c_void);
 pub type CFAllocatorPreferredSizeCallBack = unsafe extern fn(size: CFIndex, hint: CFOptionFlags, info: *const c_void) -> CFIndex;
 
-#[repr(C)] pub struct CFAllocatorContext {
-  version: CFIndex,
-  info: *mut c_void,
-  retain: CFAllocatorRetainCallBack,
-  release: CFAllocatorReleaseCallBack,
-  copyDescription: CFAllocatorCopyDescriptionCallBack,
-  allocate: CFAllocatorAllocateCallBack,
-  reallocate: CFAllocatorReallocateCallBack,
-  deallocate: CFAllocatorDeallocateCallBack,
-  preferredSize: CFAllocatorPreferredSizeCallBack
+/// Every callback may be `NULL`; without `retain` and `release`, `info` is used as is.
+#[repr(C)] #[derive(Clone, Copy)] pub struct CFAllocatorContext {
+  pub version: CFIndex,
+  pub info: *mut c_void,
+  pub retain: Option<CFAllocatorRetainCallBack>,
+  pub release: Option<CFAllocatorReleaseCallBack>,
+  pub copyDescription: Option<CFAllocatorCopyDescriptionCallBack>,
+  pub allocate: Option<CFAllocatorAllocateCallBack>,
+  pub reallocate: Option<CFAllocatorReallocateCallBack>,
+  pub deallocate: Option<CFAllocatorDeallocateCallBack>,
+  pub preferredSize: Option<CFAllocatorPreferredSizeCallBack>
+}
+
+impl Default for CFAllocatorContext {
+  fn default() -> CFAllocatorContext {
+    return CFAllocatorContext {
+      version: 0,
+      info: ptr::null_mut(),
+      retain: None,
+      release: None,
+      copyDescription: None,
+      allocate: None,
+      reallocate: None,
+      deallocate: None,
+      preferredSize: None
+    };
+  }
 }
 
 mod ext {
@@ -34,68 +51,769 @@ mod ext {
     pub fn CFAllocatorGetPreferredSizeForSize(allocator: CFAllocatorRef, size: CFIndex, hint: CFOptionFlags) -> CFIndex;
     pub fn CFAllocatorGetContext(allocator: CFAllocatorRef, context: *mut CFAllocatorContext);
 
-    pub static kCFAllocatorDefault: CFAllocatorRef;
-    pub static kCFAllocatorSystemDefault: CFAllocatorRef;
-    pub static kCFAllocatorMalloc: CFAllocatorRef;
-    pub static kCFAllocatorMallocZone: CFAllocatorRef;
-    pub static kCFAllocatorNull: CFAllocatorRef;
-    pub static kCFAllocatorUseContext: CFAllocatorRef;
+    pub static kCFAllocatorDefault: CFRef<'static, CFAllocatorRef>;
+    pub static kCFAllocatorSystemDefault: CFRef<'static, CFAllocatorRef>;
+    pub static kCFAllocatorMalloc: CFRef<'static, CFAllocatorRef>;
+    pub static kCFAllocatorMallocZone: CFRef<'static, CFAllocatorRef>;
+    pub static kCFAllocatorNull: CFRef<'static, CFAllocatorRef>;
+    pub static kCFAllocatorUseContext: CFRef<'static, CFAllocatorRef>;
   }
 }
 
-#[repr(transparent)] pub struct CFAllocatorRef(usize);
-
-unsafe impl Subtype<CFTypeRef> for CFAllocatorRef {
-  unsafe fn upcast(&self) -> CFTypeRef {
-    return CFTypeRef(self.0);
-  }
-}
-
-unsafe impl Subtype<CFAllocatorRef> for CFAllocatorRef {
-  unsafe fn upcast(&self) -> CFAllocatorRef {
-    return CFAllocatorRef(self.0);
-  }
-}
+#[cf_type(parent = CFTypeRef, type_id = CFAllocatorGetTypeID, owned = CFAllocator)]
+pub struct CFAllocatorRef;
 
 pub fn CFAllocatorGetTypeID() -> CFTypeID {
   return unsafe { ext::CFAllocatorGetTypeID() };
 }
 
-pub unsafe fn CFAllocatorSetDefault<T: Subtype<CFAllocatorRef>>(allocator: T) {
+pub unsafe fn CFAllocatorSetDefault<T: Subtype<CFAllocatorRef>>(allocator: &T) {
   return ext::CFAllocatorSetDefault(allocator.upcast());
 }
 
-pub fn CFAllocatorGetDefault() -> CFAllocatorRef {
-  return unsafe { ext::CFAllocatorGetDefault() };
+/// Retains the current thread's default allocator, which may be released once another allocator becomes the default.
+pub fn CFAllocatorGetDefault() -> CFAllocator {
+  return unsafe { CFType::from_get_rule(ext::CFAllocatorGetDefault()) };
 }
 
-pub unsafe fn CFAllocatorCreate<T: Subtype<CFAllocatorRef>>(allocator: T, context: *mut CFAllocatorContext) -> CFAllocatorRef {
-  return ext::CFAllocatorCreate(allocator.upcast(), context);
+pub unsafe fn CFAllocatorCreate<T: Subtype<CFAllocatorRef>>(allocator: &T, context: &mut CFAllocatorContext) -> Option<CFType<CFAllocatorRef>> {
+  return CFType::try_from_create_rule(ext::CFAllocatorCreate(allocator.upcast(), context));
 }
 
-pub unsafe fn CFAllocatorAllocate<T: Subtype<CFAllocatorRef>>(allocator: T, size: CFIndex, hint: CFOptionFlags) -> *mut c_void {
+pub unsafe fn CFAllocatorAllocate<T: Subtype<CFAllocatorRef>>(allocator: &T, size: CFIndex, hint: CFOptionFlags) -> *mut c_void {
   return ext::CFAllocatorAllocate(allocator.upcast(), size, hint);
 }
 
-pub unsafe fn CFAllocatorReallocate<T: Subtype<CFAllocatorRef>>(allocator: T, ptr: *mut c_void, newsize: CFIndex, hint: CFOptionFlags) -> *mut c_void {
+pub unsafe fn CFAllocatorReallocate<T: Subtype<CFAllocatorRef>>(allocator: &T, ptr: *mut c_void, newsize: CFIndex, hint: CFOptionFlags) -> *mut c_void {
   return ext::CFAllocatorReallocate(allocator.upcast(), ptr, newsize, hint);
 }
 
-pub unsafe fn CFAllocatorDeallocate<T: Subtype<CFAllocatorRef>>(allocator: T, ptr: *mut c_void) {
+pub unsafe fn CFAllocatorDeallocate<T: Subtype<CFAllocatorRef>>(allocator: &T, ptr: *mut c_void) {
   return ext::CFAllocatorDeallocate(allocator.upcast(), ptr);
 }
 
-pub fn CFAllocatorGetPreferredSizeForSize<T: Subtype<CFAllocatorRef>>(allocator: T, size: CFIndex, hint: CFOptionFlags) -> CFIndex {
+pub fn CFAllocatorGetPreferredSizeForSize<T: Subtype<CFAllocatorRef>>(allocator: &T, size: CFIndex, hint: CFOptionFlags) -> CFIndex {
   return unsafe { ext::CFAllocatorGetPreferredSizeForSize(allocator.upcast(), size, hint) };
 }
 
-pub unsafe fn CFAllocatorGetContext<T: Subtype<CFAllocatorRef>>(allocator: T, context: *mut CFAllocatorContext) {
-  return ext::CFAllocatorGetContext(allocator.upcast(), context);
+/// Fills `context` with the version 0 layout, the only one `CFAllocatorContext` describes. Any version already in
+/// `context` is ignored.
+pub fn CFAllocatorGetContext<T: Subtype<CFAllocatorRef>>(allocator: &T, context: &mut CFAllocatorContext) {
+  context.version = 0;
+
+  unsafe { ext::CFAllocatorGetContext(allocator.upcast(), context) };
+}
+
+pub static kCFAllocatorDefault: &'static CFRef<'static, CFAllocatorRef> = unsafe { &ext::kCFAllocatorDefault };
+pub static kCFAllocatorSystemDefault: &'static CFRef<'static, CFAllocatorRef> = unsafe { &ext::kCFAllocatorSystemDefault };
+pub static kCFAllocatorMalloc: &'static CFRef<'static, CFAllocatorRef> = unsafe { &ext::kCFAllocatorMalloc };
+pub static kCFAllocatorMallocZone: &'static CFRef<'static, CFAllocatorRef> = unsafe { &ext::kCFAllocatorMallocZone };
+pub static kCFAllocatorNull: &'static CFRef<'static, CFAllocatorRef> = unsafe { &ext::kCFAllocatorNull };
+pub static kCFAllocatorUseContext: &'static CFRef<'static, CFAllocatorRef> = unsafe { &ext::kCFAllocatorUseContext };
+
+impl CFAllocator {
+  /// Creates an allocator that gets its memory from `allocator`, which is dropped once the CFAllocator is released.
+  pub fn from_global_alloc<G: GlobalAlloc + Send + Sync + 'static>(allocator: G) -> CFAllocator {
+    let mut context = CFAllocatorContext {
+      info: Box::into_raw(Box::new(allocator)) as *mut c_void,
+      release: Some(global_alloc_release::<G>),
+      allocate: Some(global_alloc_allocate::<G>),
+      reallocate: Some(global_alloc_reallocate::<G>),
+      deallocate: Some(global_alloc_deallocate::<G>),
+      ..Default::default()
+    };
+
+    return unsafe { CFAllocatorCreate(kCFAllocatorSystemDefault, &mut context) }.expect("could not allocate CFAllocator");
+  }
+}
+
+pub trait CFAllocatorClass : Subtype<CFAllocatorRef> {
+  fn context(&self) -> CFAllocatorContext {
+    let mut context = CFAllocatorContext::default();
+    CFAllocatorGetContext(self, &mut context);
+
+    return context;
+  }
+}
+
+impl<T> CFAllocatorClass for T where T: Subtype<CFAllocatorRef> { }
+
+/// Makes an allocator the default until the guard is dropped, including when unwinding from a panic. The default
+/// allocator is per thread, so the guard only affects the thread that created it and cannot be sent to another.
+/// Core Foundation never releases an allocator once it has been the default, so the guard balances that when it
+/// restores the previous one. Guards have to be dropped in the reverse order they were created, which is why they are
+/// only handed out through `with_default_allocator`.
+struct CFDefaultAllocatorGuard {
+  allocator: CFAllocator,
+  previous: CFAllocator,
+  _thread: PhantomData<*const ()>
+}
+
+impl CFDefaultAllocatorGuard {
+  fn new<A: Subtype<CFAllocatorRef>>(allocator: &A) -> CFDefaultAllocatorGuard {
+    let allocator = unsafe { CFType::from_get_rule(allocator.upcast()) };
+    let previous = CFAllocatorGetDefault();
+
+    unsafe { CFAllocatorSetDefault(&allocator) };
+
+    return CFDefaultAllocatorGuard { allocator: allocator, previous: previous, _thread: PhantomData };
+  }
+}
+
+impl Drop for CFDefaultAllocatorGuard {
+  fn drop(&mut self) {
+    unsafe {
+      let (allocator, previous): (CFAllocatorRef, CFAllocatorRef) = (self.allocator.upcast(), self.previous.upcast());
+
+      if allocator.0 == previous.0 {
+        return;
+      }
+
+      // Each CFAllocatorSetDefault releases the old default once and retains the new one twice, so both allocators
+      // are left with one retain too many.
+      CFAllocatorSetDefault(&self.previous);
+      CFRelease(allocator);
+      CFRelease(previous);
+    }
+  }
+}
+
+/// Runs `f` with `allocator` as the current thread's default allocator, restoring the previous default afterwards even
+/// if `f` panics. Calls may be nested.
+pub fn with_default_allocator<A: Subtype<CFAllocatorRef>, R, F: FnOnce() -> R>(allocator: &A, f: F) -> R {
+  let _guard = CFDefaultAllocatorGuard::new(allocator);
+  return f();
+}
+
+/// Creates an allocator that gives `buffer` back to Rust when Core Foundation deallocates it, for use as a contents
+/// deallocator. Anything it has to allocate itself, such as when a mutable string outgrows `buffer`, comes from
+/// `kCFAllocatorSystemDefault`.
+crate fn buffer_deallocator<E: Copy>(buffer: Vec<E>) -> CFAllocator {
+  let mut context = CFAllocatorContext {
+    info: Box::into_raw(Box::new(Some(buffer))) as *mut c_void,
+    release: Some(buffer_release::<E>),
+    allocate: Some(buffer_allocate),
+    reallocate: Some(buffer_reallocate::<E>),
+    deallocate: Some(buffer_deallocate::<E>),
+    ..Default::default()
+  };
+
+  return unsafe { CFAllocatorCreate(kCFAllocatorSystemDefault, &mut context) }.expect("could not allocate CFAllocator");
+}
+
+fn owns_buffer<E>(buffer: &Option<Vec<E>>, ptr: *mut c_void) -> bool {
+  return buffer.as_ref().map_or(false, |buffer| buffer.as_ptr() as *mut c_void == ptr);
+}
+
+unsafe extern fn buffer_release<E>(info: *const c_void) {
+  drop(Box::from_raw(info as *mut Option<Vec<E>>));
+}
+
+unsafe extern fn buffer_allocate(allocSize: CFIndex, hint: CFOptionFlags, _info: *const c_void) -> *mut c_void {
+  return CFAllocatorAllocate(kCFAllocatorSystemDefault, allocSize, hint);
+}
+
+unsafe extern fn buffer_reallocate<E>(ptr: *mut c_void, newsize: CFIndex, hint: CFOptionFlags, info: *mut c_void) -> *mut c_void {
+  let buffer = &mut *(info as *mut Option<Vec<E>>);
+
+  if !owns_buffer(buffer, ptr) {
+    return CFAllocatorReallocate(kCFAllocatorSystemDefault, ptr, newsize, hint);
+  }
+
+  let moved = CFAllocatorAllocate(kCFAllocatorSystemDefault, newsize, hint);
+
+  if !moved.is_null() {
+    let size = buffer.as_ref().unwrap().capacity() * mem::size_of::<E>();
+    ptr::copy_nonoverlapping(ptr as *const u8, moved as *mut u8, size.min(newsize as usize));
+    *buffer = None;
+  }
+
+  return moved;
+}
+
+unsafe extern fn buffer_deallocate<E>(ptr: *mut c_void, info: *const c_void) {
+  let buffer = &mut *(info as *mut Option<Vec<E>>);
+
+  if owns_buffer(buffer, ptr) {
+    *buffer = None;
+  } else {
+    CFAllocatorDeallocate(kCFAllocatorSystemDefault, ptr);
+  }
+}
+
+/// Core Foundation only passes a pointer back when deallocating, so each block starts with its size. The header is as
+/// large as the alignment so that blocks are aligned like `malloc`'s.
+const GLOBAL_ALLOC_HEADER: usize = 16;
+
+fn global_alloc_layout(size: usize) -> Option<Layout> {
+  return size.checked_add(GLOBAL_ALLOC_HEADER).and_then(|size| Layout::from_size_align(size, GLOBAL_ALLOC_HEADER).ok());
+}
+
+unsafe extern fn global_alloc_release<G: GlobalAlloc>(info: *const c_void) {
+  drop(Box::from_raw(info as *mut G));
+}
+
+unsafe extern fn global_alloc_allocate<G: GlobalAlloc>(allocSize: CFIndex, _hint: CFOptionFlags, info: *const c_void) -> *mut c_void {
+  let layout = match global_alloc_layout(allocSize as usize) {
+    Some(layout) if allocSize >= 0 => layout,
+    _ => return ptr::null_mut()
+  };
+  let block = (*(info as *const G)).alloc(layout);
+
+  if block.is_null() {
+    return ptr::null_mut();
+  }
+
+  *(block as *mut usize) = allocSize as usize;
+  return block.offset(GLOBAL_ALLOC_HEADER as isize) as *mut c_void;
+}
+
+unsafe extern fn global_alloc_reallocate<G: GlobalAlloc>(ptr: *mut c_void, newsize: CFIndex, _hint: CFOptionFlags, info: *mut c_void) -> *mut c_void {
+  let block = (ptr as *mut u8).offset(-(GLOBAL_ALLOC_HEADER as isize));
+  let layout = global_alloc_layout(*(block as *const usize)).unwrap();
+
+  if newsize < 0 || global_alloc_layout(newsize as usize).is_none() {
+    return ptr::null_mut();
+  }
+
+  let block = (*(info as *const G)).realloc(block, layout, newsize as usize + GLOBAL_ALLOC_HEADER);
+
+  if block.is_null() {
+    return ptr::null_mut();
+  }
+
+  *(block as *mut usize) = newsize as usize;
+  return block.offset(GLOBAL_ALLOC_HEADER as isize) as *mut c_void;
+}
+
+unsafe extern fn global_alloc_deallocate<G: GlobalAlloc>(ptr: *mut c_void, info: *const c_void) {
+  let block = (ptr as *mut u8).offset(-(GLOBAL_ALLOC_HEADER as isize));
+  let layout = global_alloc_layout(*(block as *const usize)).unwrap();
+
+  (*(info as *const G)).dealloc(block, layout);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CFAllocationStats {
+  pub live_allocations: usize,
+  pub live_bytes: usize,
+  pub total_allocations: usize,
+  /// Reallocations move an existing allocation rather than adding one, so they are not part of `total_allocations`.
+  pub reallocations: usize
+}
+
+#[derive(Clone, Debug)]
+pub struct CFOutstandingAllocation {
+  pub size: usize,
+  /// Only recorded by allocators created with `CFTrackingAllocator::with_backtraces`.
+  pub backtrace: Option<Backtrace>
+}
+
+struct Tracking {
+  backtraces: bool,
+  allocations: Mutex<HashMap<usize, CFOutstandingAllocation>>,
+  stats: Mutex<CFAllocationStats>
+}
+
+impl Tracking {
+  fn record(&self, ptr: *mut c_void, size: usize) {
+    let backtrace = if self.backtraces { Some(Backtrace::new_unresolved()) } else { None };
+    let mut stats = self.stats.lock().unwrap();
+
+    stats.live_allocations += 1;
+    stats.live_bytes += size;
+    stats.total_allocations += 1;
+    self.allocations.lock().unwrap().insert(ptr as usize, CFOutstandingAllocation { size: size, backtrace: backtrace });
+  }
+
+  fn move_allocation(&self, ptr: *mut c_void, moved: *mut c_void, size: usize) {
+    let mut stats = self.stats.lock().unwrap();
+    let mut allocations = self.allocations.lock().unwrap();
+
+    stats.reallocations += 1;
+
+    if let Some(mut allocation) = allocations.remove(&(ptr as usize)) {
+      stats.live_bytes = stats.live_bytes - allocation.size + size;
+      allocation.size = size;
+      allocations.insert(moved as usize, allocation);
+    }
+  }
+
+  fn forget(&self, ptr: *mut c_void) {
+    let mut stats = self.stats.lock().unwrap();
+
+    if let Some(allocation) = self.allocations.lock().unwrap().remove(&(ptr as usize)) {
+      stats.live_allocations -= 1;
+      stats.live_bytes -= allocation.size;
+    }
+  }
+}
+
+/// An allocator that keeps count of the memory it hands out, for hunting down leaked objects. The memory itself comes
+/// from `kCFAllocatorSystemDefault`.
+pub struct CFTrackingAllocator {
+  allocator: CFAllocator,
+  tracking: Arc<Tracking>
+}
+
+impl CFTrackingAllocator {
+  pub fn new() -> CFTrackingAllocator {
+    return CFTrackingAllocator::create(false);
+  }
+
+  /// Also records where each allocation was made, which makes allocating much slower.
+  pub fn with_backtraces() -> CFTrackingAllocator {
+    return CFTrackingAllocator::create(true);
+  }
+
+  fn create(backtraces: bool) -> CFTrackingAllocator {
+    let tracking = Arc::new(Tracking { backtraces: backtraces, allocations: Mutex::new(HashMap::new()), stats: Mutex::new(CFAllocationStats::default()) });
+    let mut context = CFAllocatorContext {
+      info: Box::into_raw(Box::new(tracking.clone())) as *mut c_void,
+      release: Some(tracking_release),
+      allocate: Some(tracking_allocate),
+      reallocate: Some(tracking_reallocate),
+      deallocate: Some(tracking_deallocate),
+      ..Default::default()
+    };
+    let allocator = unsafe { CFAllocatorCreate(kCFAllocatorSystemDefault, &mut context) }.expect("could not allocate CFAllocator");
+
+    return CFTrackingAllocator { allocator: allocator, tracking: tracking };
+  }
+
+  pub fn stats(&self) -> CFAllocationStats {
+    return *self.tracking.stats.lock().unwrap();
+  }
+
+  /// Lists the allocations that have not been deallocated yet, with their backtraces resolved.
+  pub fn outstanding(&self) -> Vec<CFOutstandingAllocation> {
+    let mut outstanding: Vec<CFOutstandingAllocation> = self.tracking.allocations.lock().unwrap().values().cloned().collect();
+
+    for allocation in &mut outstanding {
+      if let Some(ref mut backtrace) = allocation.backtrace {
+        backtrace.resolve();
+      }
+    }
+
+    return outstanding;
+  }
+
+  /// Runs `f` with this as the current thread's default allocator, then reports what `outstanding` lists once the
+  /// previous default is back.
+  pub fn install<R, F: FnOnce() -> R>(&self, f: F) -> (R, Vec<CFOutstandingAllocation>) {
+    let result = with_default_allocator(&self.allocator, f);
+
+    return (result, self.outstanding());
+  }
+}
+
+unsafe impl<T> Subtype<T> for CFTrackingAllocator where CFAllocatorRef: Subtype<T> {
+  unsafe fn upcast(&self) -> T {
+    return self.allocator.upcast();
+  }
+}
+
+unsafe extern fn tracking_release(info: *const c_void) {
+  drop(Box::from_raw(info as *mut Arc<Tracking>));
+}
+
+unsafe extern fn tracking_allocate(allocSize: CFIndex, hint: CFOptionFlags, info: *const c_void) -> *mut c_void {
+  let ptr = CFAllocatorAllocate(kCFAllocatorSystemDefault, allocSize, hint);
+
+  if !ptr.is_null() {
+    (*(info as *const Arc<Tracking>)).record(ptr, allocSize as usize);
+  }
+
+  return ptr;
+}
+
+unsafe extern fn tracking_reallocate(ptr: *mut c_void, newsize: CFIndex, hint: CFOptionFlags, info: *mut c_void) -> *mut c_void {
+  let moved = CFAllocatorReallocate(kCFAllocatorSystemDefault, ptr, newsize, hint);
+
+  if !moved.is_null() {
+    (*(info as *const Arc<Tracking>)).move_allocation(ptr, moved, newsize as usize);
+  }
+
+  return moved;
+}
+
+unsafe extern fn tracking_deallocate(ptr: *mut c_void, info: *const c_void) {
+  (*(info as *const Arc<Tracking>)).forget(ptr);
+  CFAllocatorDeallocate(kCFAllocatorSystemDefault, ptr);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CFArenaStats {
+  pub chunks: usize,
+  pub reserved_bytes: usize,
+  /// Includes the per-allocation header and alignment padding.
+  pub used_bytes: usize,
+  pub allocations: usize
+}
+
+/// Each block starts with its size so that it can be copied when reallocated; the header also keeps blocks aligned
+/// like `malloc`'s.
+const ARENA_HEADER: usize = 16;
+
+struct ArenaChunk {
+  ptr: *mut u8,
+  layout: Layout
+}
+
+struct ArenaState {
+  chunks: Vec<ArenaChunk>,
+  offset: usize,
+  stats: CFArenaStats
+}
+
+unsafe impl Send for ArenaState { }
+
+struct Arena {
+  chunk_size: usize,
+  state: Mutex<ArenaState>
+}
+
+fn arena_block_size(size: usize) -> Option<usize> {
+  return size.checked_add(2 * ARENA_HEADER - 1).map(|size| size / ARENA_HEADER * ARENA_HEADER);
+}
+
+impl ArenaState {
+  unsafe fn allocate(&mut self, chunk_size: usize, size: usize) -> *mut u8 {
+    let needed = match arena_block_size(size) {
+      Some(needed) => needed,
+      None => return ptr::null_mut()
+    };
+
+    if self.chunks.last().map_or(true, |chunk| self.offset + needed > chunk.layout.size()) {
+      let layout = match Layout::from_size_align(needed.max(chunk_size), ARENA_HEADER) {
+        Ok(layout) => layout,
+        Err(_) => return ptr::null_mut()
+      };
+      let ptr = System.alloc(layout);
+
+      if ptr.is_null() {
+        return ptr::null_mut();
+      }
+
+      self.chunks.push(ArenaChunk { ptr: ptr, layout: layout });
+      self.offset = 0;
+      self.stats.chunks += 1;
+      self.stats.reserved_bytes += layout.size();
+    }
+
+    let block = self.chunks.last().unwrap().ptr.offset(self.offset as isize);
+    *(block as *mut usize) = size;
+    self.offset += needed;
+    self.stats.used_bytes += needed;
+    self.stats.allocations += 1;
+
+    return block.offset(ARENA_HEADER as isize);
+  }
+
+  unsafe fn reallocate(&mut self, chunk_size: usize, ptr: *mut u8, size: usize) -> *mut u8 {
+    let block = ptr.offset(-(ARENA_HEADER as isize));
+    let old_size = *(block as *const usize);
+    let (old_needed, needed) = match (arena_block_size(old_size), arena_block_size(size)) {
+      (Some(old_needed), Some(needed)) => (old_needed, needed),
+      _ => return ptr::null_mut()
+    };
+
+    // The most recent block can grow or shrink in place if the chunk has room.
+    if let (Some(chunk), Some(start)) = (self.chunks.last(), self.offset.checked_sub(old_needed)) {
+      if block == chunk.ptr.offset(start as isize) && start + needed <= chunk.layout.size() {
+        *(block as *mut usize) = size;
+        self.offset = start + needed;
+        self.stats.used_bytes = self.stats.used_bytes - old_needed + needed;
+
+        return ptr;
+      }
+    }
+
+    let moved = self.allocate(chunk_size, size);
+
+    if !moved.is_null() {
+      ptr::copy_nonoverlapping(ptr, moved, old_size.min(size));
+    }
+
+    return moved;
+  }
+
+  unsafe fn release_chunks(&mut self, keep: usize) {
+    for chunk in self.chunks.drain(keep..) {
+      System.dealloc(chunk.ptr, chunk.layout);
+    }
+  }
+}
+
+impl Drop for ArenaState {
+  fn drop(&mut self) {
+    unsafe { self.release_chunks(0) };
+  }
+}
+
+/// An allocator for batches of short-lived objects. It hands out memory from large chunks and never frees individual
+/// blocks; the chunks are freed together once the arena and every object allocated from it are gone.
+pub struct CFArenaAllocator {
+  allocator: CFAllocator,
+  arena: Arc<Arena>
+}
+
+impl CFArenaAllocator {
+  pub fn new() -> CFArenaAllocator {
+    return CFArenaAllocator::with_chunk_size(64 * 1024);
+  }
+
+  /// Blocks larger than `chunk_size` get a chunk of their own.
+  pub fn with_chunk_size(chunk_size: usize) -> CFArenaAllocator {
+    let arena = Arc::new(Arena { chunk_size: chunk_size, state: Mutex::new(ArenaState { chunks: Vec::new(), offset: 0, stats: CFArenaStats::default() }) });
+    let mut context = CFAllocatorContext {
+      info: Box::into_raw(Box::new(arena.clone())) as *mut c_void,
+      release: Some(arena_release),
+      allocate: Some(arena_allocate),
+      reallocate: Some(arena_reallocate),
+      deallocate: Some(arena_deallocate),
+      ..Default::default()
+    };
+    let allocator = unsafe { CFAllocatorCreate(kCFAllocatorSystemDefault, &mut context) }.expect("could not allocate CFAllocator");
+
+    return CFArenaAllocator { allocator: allocator, arena: arena };
+  }
+
+  pub fn stats(&self) -> CFArenaStats {
+    return self.arena.state.lock().unwrap().stats;
+  }
+
+  /// Frees every chunk but the first and starts handing out memory from the beginning again. Every object allocated
+  /// from the arena must already be gone.
+  pub unsafe fn reset(&self) {
+    let mut state = self.arena.state.lock().unwrap();
+    state.release_chunks(1);
+
+    let (chunks, reserved_bytes) = (state.chunks.len(), state.chunks.first().map_or(0, |chunk| chunk.layout.size()));
+    state.offset = 0;
+    state.stats = CFArenaStats { chunks: chunks, reserved_bytes: reserved_bytes, used_bytes: 0, allocations: 0 };
+  }
+}
+
+unsafe impl<T> Subtype<T> for CFArenaAllocator where CFAllocatorRef: Subtype<T> {
+  unsafe fn upcast(&self) -> T {
+    return self.allocator.upcast();
+  }
+}
+
+unsafe extern fn arena_release(info: *const c_void) {
+  drop(Box::from_raw(info as *mut Arc<Arena>));
+}
+
+unsafe extern fn arena_allocate(allocSize: CFIndex, _hint: CFOptionFlags, info: *const c_void) -> *mut c_void {
+  let arena = &*(info as *const Arc<Arena>);
+
+  if allocSize < 0 {
+    return ptr::null_mut();
+  }
+
+  return arena.state.lock().unwrap().allocate(arena.chunk_size, allocSize as usize) as *mut c_void;
 }
 
-pub static kCFAllocatorDefault: &'static CFAllocatorRef = unsafe { &ext::kCFAllocatorDefault };
-pub static kCFAllocatorSystemDefault: &'static CFAllocatorRef = unsafe { &ext::kCFAllocatorSystemDefault };
-pub static kCFAllocatorMalloc: &'static CFAllocatorRef = unsafe { &ext::kCFAllocatorMalloc };
-pub static kCFAllocatorMallocZone: &'static CFAllocatorRef = unsafe { &ext::kCFAllocatorMallocZone };
-pub static kCFAllocatorNull: &'static CFAllocatorRef = unsafe { &ext::kCFAllocatorNull };
-pub static kCFAllocatorUseContext: &'static CFAllocatorRef = unsafe { &ext::kCFAllocatorUseContext };
+unsafe extern fn arena_reallocate(ptr: *mut c_void, newsize: CFIndex, _hint: CFOptionFlags, info: *mut c_void) -> *mut c_void {
+  let arena = &*(info as *const Arc<Arena>);
+
+  if newsize < 0 {
+    return ptr::null_mut();
+  }
+
+  return arena.state.lock().unwrap().reallocate(arena.chunk_size, ptr as *mut u8, newsize as usize) as *mut c_void;
+}
+
+unsafe extern fn arena_deallocate(_ptr: *mut c_void, _info: *const c_void) {
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+  use std::panic;
+  use std::sync::atomic::AtomicUsize;
+
+  struct Counting(Arc<AtomicUsize>);
+
+  unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+      self.0.fetch_add(1, atomic::Ordering::SeqCst);
+      return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+      self.0.fetch_sub(1, atomic::Ordering::SeqCst);
+      System.dealloc(ptr, layout);
+    }
+  }
+
+  #[test]
+  fn it_allocates_from_global_alloc() {
+    let live = Arc::new(AtomicUsize::new(0));
+    let allocator = CFAllocator::from_global_alloc(Counting(live.clone()));
+
+    unsafe {
+      let ptr = CFAllocatorAllocate(&allocator, 3, CFOptionFlags(0)) as *mut u8;
+      assert_eq!(ptr as usize % 16, 0);
+      ptr::copy_nonoverlapping(b"abc".as_ptr(), ptr, 3);
+
+      let ptr = CFAllocatorReallocate(&allocator, ptr as *mut c_void, 4096, CFOptionFlags(0)) as *mut u8;
+      assert_eq!(slice::from_raw_parts(ptr, 3), b"abc");
+      assert_eq!(live.load(atomic::Ordering::SeqCst), 1);
+
+      CFAllocatorDeallocate(&allocator, ptr as *mut c_void);
+      assert_eq!(live.load(atomic::Ordering::SeqCst), 0);
+    }
+
+    let string = CFStringCreateWithBytes(&allocator, b"hagane", CFStringEncoding::kCFStringEncodingUTF8, false).unwrap();
+    assert!(live.load(atomic::Ordering::SeqCst) > 0);
+    assert_eq!(string.to_string(), "hagane");
+
+    drop(string);
+    assert_eq!(live.load(atomic::Ordering::SeqCst), 0);
+
+    drop(allocator);
+    assert_eq!(Arc::strong_count(&live), 1);
+  }
+
+  #[test]
+  fn it_reads_contexts() {
+    let allocator = CFAllocator::from_global_alloc(System);
+    let context = allocator.context();
+    assert!(context.allocate.is_some() && context.deallocate.is_some());
+    assert!(context.retain.is_none() && context.copyDescription.is_none() && context.preferredSize.is_none());
+
+    let context = CFAllocatorContext::default();
+    assert!(context.info.is_null() && context.allocate.is_none());
+
+    let mut context = CFAllocatorContext { version: 1, ..Default::default() };
+    CFAllocatorGetContext(&allocator, &mut context);
+    assert!(context.version == 0 && context.allocate.is_some());
+  }
+
+  #[test]
+  fn it_tracks_allocations() {
+    let tracker = CFTrackingAllocator::with_backtraces();
+
+    unsafe {
+      let ptr = CFAllocatorAllocate(&tracker, 16, CFOptionFlags(0));
+      let ptr = CFAllocatorReallocate(&tracker, ptr, 64, CFOptionFlags(0));
+      assert_eq!(tracker.stats(), CFAllocationStats { live_allocations: 1, live_bytes: 64, total_allocations: 1, reallocations: 1 });
+
+      let outstanding = tracker.outstanding();
+      assert_eq!(outstanding.len(), 1);
+      assert_eq!(outstanding[0].size, 64);
+      assert!(outstanding[0].backtrace.is_some());
+
+      let ptr = CFAllocatorReallocate(&tracker, ptr, 32, CFOptionFlags(0));
+      assert_eq!(tracker.stats(), CFAllocationStats { live_allocations: 1, live_bytes: 32, total_allocations: 1, reallocations: 2 });
+
+      CFAllocatorDeallocate(&tracker, ptr);
+      assert_eq!(tracker.stats().live_bytes, 0);
+    }
+
+    let ((), _) = tracker.install(|| {
+      assert!(CFEqual(&CFAllocatorGetDefault(), &tracker) == Boolean::TRUE);
+
+      let string = CFString::from_str("hagane");
+      assert!(tracker.stats().live_allocations > 0);
+      assert!(CFEqual(&CFGetAllocator(&string), &tracker) == Boolean::TRUE);
+    });
+
+    assert!(CFEqual(&CFAllocatorGetDefault(), &tracker) == Boolean::FALSE);
+  }
+
+  #[test]
+  fn it_reports_leaks_when_uninstalled() {
+    let tracker = CFTrackingAllocator::new();
+
+    let (ptr, outstanding) = tracker.install(|| unsafe { CFAllocatorAllocate(&tracker, 24, CFOptionFlags(0)) });
+    assert_eq!(outstanding.len(), 1);
+    assert_eq!(outstanding[0].size, 24);
+    assert!(outstanding[0].backtrace.is_none());
+
+    unsafe { CFAllocatorDeallocate(&tracker, ptr) };
+    let ((), outstanding) = tracker.install(|| ());
+    assert!(outstanding.is_empty());
+  }
+
+  #[test]
+  fn it_restores_the_default_allocator() {
+    let allocator = CFAllocator::from_global_alloc(System);
+    let previous = CFAllocatorGetDefault();
+    let retain_count = CFGetRetainCount(&allocator);
+
+    let length = with_default_allocator(&allocator, || {
+      assert!(CFEqual(&CFAllocatorGetDefault(), &allocator) == Boolean::TRUE);
+      return CFString::from_str("hagane").get_length();
+    });
+    assert_eq!(length, 6);
+    assert!(CFEqual(&CFAllocatorGetDefault(), &previous) == Boolean::TRUE);
+    assert_eq!(CFGetRetainCount(&allocator), retain_count);
+
+    let result = panic::catch_unwind(|| with_default_allocator(&allocator, || panic!("unwinding")));
+    assert!(result.is_err());
+    assert!(CFEqual(&CFAllocatorGetDefault(), &previous) == Boolean::TRUE);
+    assert_eq!(CFGetRetainCount(&allocator), retain_count);
+
+    let default = with_default_allocator(&allocator, CFAllocatorGetDefault);
+    assert!(CFEqual(&default, &allocator) == Boolean::TRUE);
+    assert_eq!(CFGetRetainCount(&allocator), retain_count + 1);
+  }
+
+  #[test]
+  fn it_nests_default_allocators() {
+    let outer = CFAllocator::from_global_alloc(System);
+    let inner = CFArenaAllocator::new();
+    let previous = CFAllocatorGetDefault();
+    let retain_counts = (CFGetRetainCount(&outer), CFGetRetainCount(&inner));
+
+    with_default_allocator(&outer, || {
+      with_default_allocator(&inner, || {
+        assert!(CFEqual(&CFAllocatorGetDefault(), &inner) == Boolean::TRUE);
+      });
+      assert!(CFEqual(&CFAllocatorGetDefault(), &outer) == Boolean::TRUE);
+
+      with_default_allocator(&outer, || {
+        assert!(CFEqual(&CFAllocatorGetDefault(), &outer) == Boolean::TRUE);
+      });
+      assert!(CFEqual(&CFAllocatorGetDefault(), &outer) == Boolean::TRUE);
+    });
+
+    assert!(CFEqual(&CFAllocatorGetDefault(), &previous) == Boolean::TRUE);
+    assert_eq!((CFGetRetainCount(&outer), CFGetRetainCount(&inner)), retain_counts);
+  }
+
+  #[test]
+  fn it_allocates_from_arenas() {
+    let arena = CFArenaAllocator::with_chunk_size(256);
+
+    unsafe {
+      let first = CFAllocatorAllocate(&arena, 20, CFOptionFlags(0)) as *mut u8;
+      ptr::copy_nonoverlapping(b"abc".as_ptr(), first, 3);
+      let second = CFAllocatorAllocate(&arena, 10, CFOptionFlags(0));
+      assert_eq!(second as usize % 16, 0);
+      assert_eq!(arena.stats(), CFArenaStats { chunks: 1, reserved_bytes: 256, used_bytes: 80, allocations: 2 });
+
+      assert_eq!(CFAllocatorReallocate(&arena, second, 40, CFOptionFlags(0)), second);
+      assert_eq!(arena.stats().used_bytes, 112);
+
+      let moved = CFAllocatorReallocate(&arena, first as *mut c_void, 30, CFOptionFlags(0)) as *mut u8;
+      assert!(moved != first);
+      assert_eq!(slice::from_raw_parts(moved, 3), b"abc");
+
+      CFAllocatorDeallocate(&arena, moved as *mut c_void);
+      CFAllocatorAllocate(&arena, 1000, CFOptionFlags(0));
+      assert_eq!(arena.stats().chunks, 2);
+      assert_eq!(arena.stats().allocations, 4);
+
+      arena.reset();
+      assert_eq!(arena.stats(), CFArenaStats { chunks: 1, reserved_bytes: 256, used_bytes: 0, allocations: 0 });
+    }
+
+    let strings: Vec<CFString> = with_default_allocator(&arena, || (0..100).map(|i| CFString::from_str(&i.to_string())).collect());
+    assert!(arena.stats().allocations >= 100);
+    assert_eq!(strings[42].to_string(), "42");
+  }
+}
@@ -0,0 +1,211 @@
+use crate::*;
+
+pub type CFArrayRetainCallBack = unsafe extern fn(allocator: CFAllocatorRef, value: *const c_void) -> *const c_void;
+pub type CFArrayReleaseCallBack = unsafe extern fn(allocator: CFAllocatorRef, value: *const c_void);
+pub type CFArrayCopyDescriptionCallBack = unsafe extern fn(value: *const c_void) -> CFStringRef;
+pub type CFArrayEqualCallBack = unsafe extern fn(value1: *const c_void, value2: *const c_void) -> Boolean;
+
+#[repr(C)] pub struct CFArrayCallBacks {
+  pub version: CFIndex,
+  pub retain: Option<CFArrayRetainCallBack>,
+  pub release: Option<CFArrayReleaseCallBack>,
+  pub copyDescription: Option<CFArrayCopyDescriptionCallBack>,
+  pub equal: Option<CFArrayEqualCallBack>
+}
+
+mod ext {
+  use crate::*;
+
+  extern {
+    pub fn CFArrayGetTypeID() -> CFTypeID;
+
+    pub fn CFArrayCreate(allocator: CFAllocatorRef, values: *const *const c_void, numValues: CFIndex, callBacks: *const CFArrayCallBacks) -> CFArrayRef;
+    // CFArrayRef CFArrayCreateCopy(CFAllocatorRef allocator, CFArrayRef theArray);
+    // CFMutableArrayRef CFArrayCreateMutable(CFAllocatorRef allocator, CFIndex capacity, const CFArrayCallBacks *callBacks);
+    // CFMutableArrayRef CFArrayCreateMutableCopy(CFAllocatorRef allocator, CFIndex capacity, CFArrayRef theArray);
+    pub fn CFArrayGetCount(theArray: CFArrayRef) -> CFIndex;
+    // CFIndex CFArrayGetCountOfValue(CFArrayRef theArray, CFRange range, const void *value);
+    // Boolean CFArrayContainsValue(CFArrayRef theArray, CFRange range, const void *value);
+    pub fn CFArrayGetValueAtIndex(theArray: CFArrayRef, idx: CFIndex) -> *const c_void;
+    // void CFArrayGetValues(CFArrayRef theArray, CFRange range, const void **values);
+    // void CFArrayApplyFunction(CFArrayRef theArray, CFRange range, CFArrayApplierFunction applier, void *context);
+    // CFIndex CFArrayGetFirstIndexOfValue(CFArrayRef theArray, CFRange range, const void *value);
+    // CFIndex CFArrayGetLastIndexOfValue(CFArrayRef theArray, CFRange range, const void *value);
+    // CFIndex CFArrayBSearchValues(CFArrayRef theArray, CFRange range, const void *value, CFComparatorFunction comparator, void *context);
+    // void CFArrayAppendValue(CFMutableArrayRef theArray, const void *value);
+    // void CFArrayInsertValueAtIndex(CFMutableArrayRef theArray, CFIndex idx, const void *value);
+    // void CFArraySetValueAtIndex(CFMutableArrayRef theArray, CFIndex idx, const void *value);
+    // void CFArrayRemoveValueAtIndex(CFMutableArrayRef theArray, CFIndex idx);
+    // void CFArrayRemoveAllValues(CFMutableArrayRef theArray);
+    // void CFArrayReplaceValues(CFMutableArrayRef theArray, CFRange range, const void **newValues, CFIndex newCount);
+    // void CFArrayExchangeValuesAtIndices(CFMutableArrayRef theArray, CFIndex idx1, CFIndex idx2);
+    // void CFArraySortValues(CFMutableArrayRef theArray, CFRange range, CFComparatorFunction comparator, void *context);
+    // void CFArrayAppendArray(CFMutableArrayRef theArray, CFArrayRef otherArray, CFRange otherRange);
+
+    pub static kCFTypeArrayCallBacks: CFArrayCallBacks;
+  }
+}
+
+#[cf_type(parent = CFTypeRef, type_id = CFArrayGetTypeID)]
+pub struct CFArrayRef;
+
+pub static kCFTypeArrayCallBacks: &'static CFArrayCallBacks = unsafe { &ext::kCFTypeArrayCallBacks };
+
+pub fn CFArrayGetTypeID() -> CFTypeID {
+  return unsafe { ext::CFArrayGetTypeID() };
+}
+
+pub unsafe fn CFArrayCreate<A: Subtype<CFAllocatorRef>>(allocator: &A, values: &[*const c_void], callBacks: Option<&CFArrayCallBacks>) -> Option<CFType<CFArrayRef>> {
+  let callBacks = callBacks.map_or(ptr::null(), |callBacks| callBacks as *const CFArrayCallBacks);
+
+  return CFType::try_from_create_rule(ext::CFArrayCreate(allocator.upcast(), values.as_ptr(), values.len() as CFIndex, callBacks));
+}
+
+pub fn CFArrayGetCount<A: Subtype<CFArrayRef>>(theArray: &A) -> CFIndex {
+  return unsafe { ext::CFArrayGetCount(theArray.upcast()) };
+}
+
+/// The value is whatever the array's callbacks store, so interpreting it is up to the caller.
+pub fn CFArrayGetValueAtIndex<A: Subtype<CFArrayRef>>(theArray: &A, idx: CFIndex) -> *const c_void {
+  assert!(idx >= 0 && idx < CFArrayGetCount(theArray), "index out of bounds");
+
+  return unsafe { ext::CFArrayGetValueAtIndex(theArray.upcast(), idx) };
+}
+
+pub trait CFArrayClass : Subtype<CFArrayRef> {
+  fn get_count(&self) -> CFIndex {
+    return CFArrayGetCount(self);
+  }
+}
+
+impl<T> CFArrayClass for T where T: Subtype<CFArrayRef> { }
+
+/// An owned array whose elements are all Core Foundation objects of type `T`.
+#[repr(transparent)] pub struct CFArray<T>(CFType<CFArrayRef>, PhantomData<T>);
+
+impl<T: Downcast<CFTypeRef, TypeID = CFTypeID>> CFArray<T> {
+  pub fn from_slice<E: Subtype<T>>(values: &[E]) -> CFArray<T> {
+    let values: Vec<*const c_void> = values.iter().map(|value| unsafe {
+      let value: T = value.upcast();
+      let cf: CFTypeRef = value.upcast();
+
+      cf.0 as *const c_void
+    }).collect();
+    let array = unsafe { CFArrayCreate(kCFAllocatorDefault, &values, Some(kCFTypeArrayCallBacks)) };
+
+    return CFArray(array.expect("could not allocate CFArray"), PhantomData);
+  }
+
+  /// Checks that every element of `array` is a `T`.
+  ///
+  /// The check reads the type of each element, so the caller must ensure they are all Core Foundation objects, as in
+  /// an array created with `kCFTypeArrayCallBacks`.
+  pub unsafe fn from_untyped(array: CFType<CFArrayRef>) -> Result<CFArray<T>, TypeMismatch> {
+    for index in 0..array.get_count() {
+      let found = CFGetTypeID(&CFTypeRef(CFArrayGetValueAtIndex(&array, index) as usize));
+
+      if found != T::type_id() {
+        return Err(TypeMismatch { expected: T::type_id(), found: found });
+      }
+    }
+
+    return Ok(CFArray(array, PhantomData));
+  }
+
+  /// Like `from_untyped`, but trusts the caller that every element is a `T`.
+  pub unsafe fn from_untyped_unchecked(array: CFType<CFArrayRef>) -> CFArray<T> {
+    return CFArray(array, PhantomData);
+  }
+
+  pub fn into_untyped(self) -> CFType<CFArrayRef> {
+    return self.0;
+  }
+
+  pub fn len(&self) -> usize {
+    return self.get_count() as usize;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.len() == 0;
+  }
+
+  pub fn get<'a>(&'a self, index: usize) -> Option<CFRef<'a, T>> {
+    if index >= self.len() {
+      return None;
+    }
+
+    let value = CFArrayGetValueAtIndex(self, index as CFIndex);
+    return Some(unsafe { CFRef::from_get_rule(T::downcast_unchecked(&CFTypeRef(value as usize))) });
+  }
+
+  pub fn iter<'a>(&'a self) -> CFArrayIter<'a, T> {
+    return CFArrayIter { array: self, index: 0 };
+  }
+}
+
+unsafe impl<T, U> Subtype<U> for CFArray<T> where CFArrayRef: Subtype<U> {
+  unsafe fn upcast(&self) -> U {
+    return self.0.upcast();
+  }
+}
+
+impl<T> Clone for CFArray<T> {
+  fn clone(&self) -> CFArray<T> {
+    return CFArray(self.0.clone(), PhantomData);
+  }
+}
+
+impl<'a, T: Downcast<CFTypeRef, TypeID = CFTypeID>> IntoIterator for &'a CFArray<T> {
+  type Item = CFRef<'a, T>;
+  type IntoIter = CFArrayIter<'a, T>;
+
+  fn into_iter(self) -> CFArrayIter<'a, T> {
+    return self.iter();
+  }
+}
+
+pub struct CFArrayIter<'a, T: 'a> {
+  array: &'a CFArray<T>,
+  index: usize
+}
+
+impl<'a, T: Downcast<CFTypeRef, TypeID = CFTypeID>> Iterator for CFArrayIter<'a, T> {
+  type Item = CFRef<'a, T>;
+
+  fn next(&mut self) -> Option<CFRef<'a, T>> {
+    let value = self.array.get(self.index)?;
+    self.index += 1;
+
+    return Some(value);
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = self.array.len() - self.index;
+    return (remaining, Some(remaining));
+  }
+}
+
+impl<'a, T: Downcast<CFTypeRef, TypeID = CFTypeID>> ExactSizeIterator for CFArrayIter<'a, T> { }
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn it_creates_arrays() {
+    let array = CFArray::<CFStringRef>::from_slice(&[CFString::from_str("a"), CFString::from_str("b")]);
+    assert_eq!(array.len(), 2);
+    assert_eq!(array.get(1).unwrap().to_string(), "b");
+    assert!(array.get(2).is_none());
+    assert_eq!(array.iter().map(|string| string.to_string()).collect::<Vec<_>>(), ["a", "b"]);
+  }
+
+  #[test]
+  fn it_checks_element_types() {
+    let strings = CFArray::<CFStringRef>::from_slice(&[CFString::from_str("a")]).into_untyped();
+    assert!(unsafe { CFArray::<CFStringRef>::from_untyped(strings.clone()) }.is_ok());
+
+    let error = unsafe { CFArray::<CFNullRef>::from_untyped(strings) }.err().unwrap();
+    assert_eq!(error, TypeMismatch { expected: CFNullGetTypeID(), found: CFStringGetTypeID() });
+  }
+}
@@ -0,0 +1,119 @@
+use crate::*;
+
+#[repr(i64)] #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CFCharacterSetPredefinedSet {
+  kCFCharacterSetControl = 1,
+  kCFCharacterSetWhitespace = 2,
+  kCFCharacterSetWhitespaceAndNewline = 3,
+  kCFCharacterSetDecimalDigit = 4,
+  kCFCharacterSetLetter = 5,
+  kCFCharacterSetLowercaseLetter = 6,
+  kCFCharacterSetUppercaseLetter = 7,
+  kCFCharacterSetNonBase = 8,
+  kCFCharacterSetDecomposable = 9,
+  kCFCharacterSetAlphaNumeric = 10,
+  kCFCharacterSetPunctuation = 11,
+  kCFCharacterSetIllegal = 12,
+  kCFCharacterSetCapitalizedLetter = 13,
+  kCFCharacterSetSymbol = 14,
+  kCFCharacterSetNewline = 15
+}
+
+mod ext {
+  use crate::*;
+
+  extern {
+    pub fn CFCharacterSetGetTypeID() -> CFTypeID;
+
+    pub fn CFCharacterSetGetPredefined(theSetIdentifier: CFCharacterSetPredefinedSet) -> CFCharacterSetRef;
+    pub fn CFCharacterSetCreateWithCharactersInRange(alloc: CFAllocatorRef, theRange: CFRange) -> CFCharacterSetRef;
+    pub fn CFCharacterSetCreateWithCharactersInString(alloc: CFAllocatorRef, theString: CFStringRef) -> CFCharacterSetRef;
+    // CFCharacterSetRef CFCharacterSetCreateWithBitmapRepresentation(CFAllocatorRef alloc, CFDataRef theData);
+    pub fn CFCharacterSetCreateInvertedSet(alloc: CFAllocatorRef, theSet: CFCharacterSetRef) -> CFCharacterSetRef;
+    // Boolean CFCharacterSetIsSupersetOfSet(CFCharacterSetRef theSet, CFCharacterSetRef theOtherset);
+    // Boolean CFCharacterSetHasMemberInPlane(CFCharacterSetRef theSet, CFIndex thePlane);
+    // CFMutableCharacterSetRef CFCharacterSetCreateMutable(CFAllocatorRef alloc);
+    // CFCharacterSetRef CFCharacterSetCreateCopy(CFAllocatorRef alloc, CFCharacterSetRef theSet);
+    // CFMutableCharacterSetRef CFCharacterSetCreateMutableCopy(CFAllocatorRef alloc, CFCharacterSetRef theSet);
+    pub fn CFCharacterSetIsCharacterMember(theSet: CFCharacterSetRef, theChar: UniChar) -> Boolean;
+    pub fn CFCharacterSetIsLongCharacterMember(theSet: CFCharacterSetRef, theChar: UTF32Char) -> Boolean;
+    // CFDataRef CFCharacterSetCreateBitmapRepresentation(CFAllocatorRef alloc, CFCharacterSetRef theSet);
+    // void CFCharacterSetAddCharactersInRange(CFMutableCharacterSetRef theSet, CFRange theRange);
+    // void CFCharacterSetRemoveCharactersInRange(CFMutableCharacterSetRef theSet, CFRange theRange);
+    // void CFCharacterSetAddCharactersInString(CFMutableCharacterSetRef theSet,  CFStringRef theString);
+    // void CFCharacterSetRemoveCharactersInString(CFMutableCharacterSetRef theSet, CFStringRef theString);
+    // void CFCharacterSetUnion(CFMutableCharacterSetRef theSet, CFCharacterSetRef theOtherSet);
+    // void CFCharacterSetIntersect(CFMutableCharacterSetRef theSet, CFCharacterSetRef theOtherSet);
+    // void CFCharacterSetInvert(CFMutableCharacterSetRef theSet);
+  }
+}
+
+#[cf_type(parent = CFTypeRef, type_id = CFCharacterSetGetTypeID, owned = CFCharacterSet)]
+pub struct CFCharacterSetRef;
+
+pub fn CFCharacterSetGetTypeID() -> CFTypeID {
+  return unsafe { ext::CFCharacterSetGetTypeID() };
+}
+
+pub fn CFCharacterSetGetPredefined(theSetIdentifier: CFCharacterSetPredefinedSet) -> CFRef<'static, CFCharacterSetRef> {
+  return unsafe { CFRef::from_get_rule(ext::CFCharacterSetGetPredefined(theSetIdentifier)) };
+}
+
+pub fn CFCharacterSetCreateWithCharactersInRange<A: Subtype<CFAllocatorRef>>(alloc: &A, theRange: CFRange) -> Option<CFCharacterSet> {
+  return unsafe { CFType::try_from_create_rule(ext::CFCharacterSetCreateWithCharactersInRange(alloc.upcast(), theRange)) };
+}
+
+pub fn CFCharacterSetCreateWithCharactersInString<A: Subtype<CFAllocatorRef>, S: Subtype<CFStringRef>>(alloc: &A, theString: &S) -> Option<CFCharacterSet> {
+  return unsafe { CFType::try_from_create_rule(ext::CFCharacterSetCreateWithCharactersInString(alloc.upcast(), theString.upcast())) };
+}
+
+pub fn CFCharacterSetCreateInvertedSet<A: Subtype<CFAllocatorRef>, C: Subtype<CFCharacterSetRef>>(alloc: &A, theSet: &C) -> Option<CFCharacterSet> {
+  return unsafe { CFType::try_from_create_rule(ext::CFCharacterSetCreateInvertedSet(alloc.upcast(), theSet.upcast())) };
+}
+
+pub fn CFCharacterSetIsCharacterMember<C: Subtype<CFCharacterSetRef>>(theSet: &C, theChar: UniChar) -> Boolean {
+  return unsafe { ext::CFCharacterSetIsCharacterMember(theSet.upcast(), theChar) };
+}
+
+pub fn CFCharacterSetIsLongCharacterMember<C: Subtype<CFCharacterSetRef>>(theSet: &C, theChar: UTF32Char) -> Boolean {
+  return unsafe { ext::CFCharacterSetIsLongCharacterMember(theSet.upcast(), theChar) };
+}
+
+impl CFCharacterSet {
+  pub fn predefined(set: CFCharacterSetPredefinedSet) -> CFRef<'static, CFCharacterSetRef> {
+    return CFCharacterSetGetPredefined(set);
+  }
+
+  pub fn from_chars(chars: &str) -> CFCharacterSet {
+    return CFCharacterSetCreateWithCharactersInString(kCFAllocatorDefault, &CFString::from_str(chars)).expect("could not allocate CFCharacterSet");
+  }
+}
+
+pub trait CFCharacterSetClass : Subtype<CFCharacterSetRef> {
+  fn contains(&self, c: char) -> bool {
+    return CFCharacterSetIsLongCharacterMember(self, c as UTF32Char) == Boolean::TRUE;
+  }
+
+  fn create_inverted_set(&self) -> CFCharacterSet {
+    return CFCharacterSetCreateInvertedSet(kCFAllocatorDefault, self).expect("could not allocate CFCharacterSet");
+  }
+}
+
+impl<T> CFCharacterSetClass for T where T: Subtype<CFCharacterSetRef> { }
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn it_tests_membership() {
+    let digits = CFCharacterSet::predefined(CFCharacterSetPredefinedSet::kCFCharacterSetDecimalDigit);
+    assert!(digits.contains('7'));
+    assert!(!digits.contains('x'));
+    assert!(digits.create_inverted_set().contains('x'));
+
+    let vowels = CFCharacterSet::from_chars("aeiouö");
+    assert!(vowels.contains('ö'));
+    assert!(!vowels.contains('z'));
+  }
+}
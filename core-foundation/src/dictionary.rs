@@ -0,0 +1,123 @@
+use crate::*;
+
+pub type CFDictionaryRetainCallBack = unsafe extern fn(allocator: CFAllocatorRef, value: *const c_void) -> *const c_void;
+pub type CFDictionaryReleaseCallBack = unsafe extern fn(allocator: CFAllocatorRef, value: *const c_void);
+pub type CFDictionaryCopyDescriptionCallBack = unsafe extern fn(value: *const c_void) -> CFStringRef;
+pub type CFDictionaryEqualCallBack = unsafe extern fn(value1: *const c_void, value2: *const c_void) -> Boolean;
+pub type CFDictionaryHashCallBack = unsafe extern fn(value: *const c_void) -> CFHashCode;
+
+#[repr(C)] pub struct CFDictionaryKeyCallBacks {
+  pub version: CFIndex,
+  pub retain: Option<CFDictionaryRetainCallBack>,
+  pub release: Option<CFDictionaryReleaseCallBack>,
+  pub copyDescription: Option<CFDictionaryCopyDescriptionCallBack>,
+  pub equal: Option<CFDictionaryEqualCallBack>,
+  pub hash: Option<CFDictionaryHashCallBack>
+}
+
+#[repr(C)] pub struct CFDictionaryValueCallBacks {
+  pub version: CFIndex,
+  pub retain: Option<CFDictionaryRetainCallBack>,
+  pub release: Option<CFDictionaryReleaseCallBack>,
+  pub copyDescription: Option<CFDictionaryCopyDescriptionCallBack>,
+  pub equal: Option<CFDictionaryEqualCallBack>
+}
+
+mod ext {
+  use crate::*;
+
+  extern {
+    pub fn CFDictionaryGetTypeID() -> CFTypeID;
+
+    pub fn CFDictionaryCreate(allocator: CFAllocatorRef, keys: *const *const c_void, values: *const *const c_void, numValues: CFIndex, keyCallBacks: *const CFDictionaryKeyCallBacks, valueCallBacks: *const CFDictionaryValueCallBacks) -> CFDictionaryRef;
+    // CFDictionaryRef CFDictionaryCreateCopy(CFAllocatorRef allocator, CFDictionaryRef theDict);
+    // CFMutableDictionaryRef CFDictionaryCreateMutable(CFAllocatorRef allocator, CFIndex capacity, const CFDictionaryKeyCallBacks *keyCallBacks, const CFDictionaryValueCallBacks *valueCallBacks);
+    // CFMutableDictionaryRef CFDictionaryCreateMutableCopy(CFAllocatorRef allocator, CFIndex capacity, CFDictionaryRef theDict);
+    pub fn CFDictionaryGetCount(theDict: CFDictionaryRef) -> CFIndex;
+    // CFIndex CFDictionaryGetCountOfKey(CFDictionaryRef theDict, const void *key);
+    // CFIndex CFDictionaryGetCountOfValue(CFDictionaryRef theDict, const void *value);
+    // Boolean CFDictionaryContainsKey(CFDictionaryRef theDict, const void *key);
+    // Boolean CFDictionaryContainsValue(CFDictionaryRef theDict, const void *value);
+    pub fn CFDictionaryGetValue(theDict: CFDictionaryRef, key: *const c_void) -> *const c_void;
+    // Boolean CFDictionaryGetValueIfPresent(CFDictionaryRef theDict, const void *key, const void **value);
+    // void CFDictionaryGetKeysAndValues(CFDictionaryRef theDict, const void **keys, const void **values);
+    // void CFDictionaryApplyFunction(CFDictionaryRef theDict, CFDictionaryApplierFunction applier, void *context);
+    // void CFDictionaryAddValue(CFMutableDictionaryRef theDict, const void *key, const void *value);
+    // void CFDictionarySetValue(CFMutableDictionaryRef theDict, const void *key, const void *value);
+    // void CFDictionaryReplaceValue(CFMutableDictionaryRef theDict, const void *key, const void *value);
+    // void CFDictionaryRemoveValue(CFMutableDictionaryRef theDict, const void *key);
+    // void CFDictionaryRemoveAllValues(CFMutableDictionaryRef theDict);
+
+    pub static kCFTypeDictionaryKeyCallBacks: CFDictionaryKeyCallBacks;
+    pub static kCFCopyStringDictionaryKeyCallBacks: CFDictionaryKeyCallBacks;
+    pub static kCFTypeDictionaryValueCallBacks: CFDictionaryValueCallBacks;
+  }
+}
+
+#[cf_type(parent = CFTypeRef, type_id = CFDictionaryGetTypeID, owned = CFDictionary)]
+pub struct CFDictionaryRef;
+
+pub static kCFTypeDictionaryKeyCallBacks: &'static CFDictionaryKeyCallBacks = unsafe { &ext::kCFTypeDictionaryKeyCallBacks };
+pub static kCFCopyStringDictionaryKeyCallBacks: &'static CFDictionaryKeyCallBacks = unsafe { &ext::kCFCopyStringDictionaryKeyCallBacks };
+pub static kCFTypeDictionaryValueCallBacks: &'static CFDictionaryValueCallBacks = unsafe { &ext::kCFTypeDictionaryValueCallBacks };
+
+pub fn CFDictionaryGetTypeID() -> CFTypeID {
+  return unsafe { ext::CFDictionaryGetTypeID() };
+}
+
+pub unsafe fn CFDictionaryCreate<A: Subtype<CFAllocatorRef>>(allocator: &A, keys: &[*const c_void], values: &[*const c_void], keyCallBacks: Option<&CFDictionaryKeyCallBacks>, valueCallBacks: Option<&CFDictionaryValueCallBacks>) -> Option<CFDictionary> {
+  assert_eq!(keys.len(), values.len(), "keys and values differ in length");
+
+  let keyCallBacks = keyCallBacks.map_or(ptr::null(), |callBacks| callBacks as *const CFDictionaryKeyCallBacks);
+  let valueCallBacks = valueCallBacks.map_or(ptr::null(), |callBacks| callBacks as *const CFDictionaryValueCallBacks);
+
+  return CFType::try_from_create_rule(ext::CFDictionaryCreate(allocator.upcast(), keys.as_ptr(), values.as_ptr(), keys.len() as CFIndex, keyCallBacks, valueCallBacks));
+}
+
+pub fn CFDictionaryGetCount<D: Subtype<CFDictionaryRef>>(theDict: &D) -> CFIndex {
+  return unsafe { ext::CFDictionaryGetCount(theDict.upcast()) };
+}
+
+/// Returns `NULL` if the key is not present, which is indistinguishable from a `NULL` value.
+pub unsafe fn CFDictionaryGetValue<D: Subtype<CFDictionaryRef>>(theDict: &D, key: *const c_void) -> *const c_void {
+  return ext::CFDictionaryGetValue(theDict.upcast(), key);
+}
+
+impl CFDictionary {
+  /// Creates a dictionary that retains its keys and values and compares them with `CFEqual`.
+  pub fn from_pairs<K: Subtype<CFTypeRef>, V: Subtype<CFTypeRef>>(pairs: &[(K, V)]) -> CFDictionary {
+    let keys: Vec<*const c_void> = pairs.iter().map(|&(ref key, _)| unsafe { key.upcast().0 as *const c_void }).collect();
+    let values: Vec<*const c_void> = pairs.iter().map(|&(_, ref value)| unsafe { value.upcast().0 as *const c_void }).collect();
+
+    return unsafe { CFDictionaryCreate(kCFAllocatorDefault, &keys, &values, Some(kCFTypeDictionaryKeyCallBacks), Some(kCFTypeDictionaryValueCallBacks)) }.expect("could not allocate CFDictionary");
+  }
+}
+
+pub trait CFDictionaryClass : Subtype<CFDictionaryRef> {
+  fn get_count(&self) -> CFIndex {
+    return CFDictionaryGetCount(self);
+  }
+
+  /// Looks up `key` in a dictionary. The caller must ensure the values are Core Foundation objects, as in a
+  /// dictionary created with `kCFTypeDictionaryValueCallBacks`.
+  unsafe fn get<'a, K: Subtype<CFTypeRef>>(&'a self, key: &K) -> Option<CFRef<'a, CFTypeRef>> {
+    return CFRef::try_from_get_rule(CFTypeRef(CFDictionaryGetValue(self, key.upcast().0 as *const c_void) as usize));
+  }
+}
+
+impl<T> CFDictionaryClass for T where T: Subtype<CFDictionaryRef> { }
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn it_looks_up_values() {
+    let dictionary = CFDictionary::from_pairs(&[(CFString::from_str("a"), CFString::from_str("1")), (CFString::from_str("b"), CFString::from_str("2"))]);
+    assert_eq!(dictionary.get_count(), 2);
+
+    let value = unsafe { dictionary.get(&CFString::from_str("b")) }.unwrap();
+    assert_eq!(value.downcast::<CFStringRef>().unwrap().to_string(), "2");
+    assert!(unsafe { dictionary.get(&CFString::from_str("c")) }.is_none());
+  }
+}
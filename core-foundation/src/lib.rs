@@ -1,15 +1,18 @@
 #![feature(crate_in_paths)]
 #![feature(crate_visibility_modifier)]
 #![feature(repr_transparent)]
+#![feature(try_from)]
 
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 
+extern crate backtrace;
 extern crate hagane_core;
+#[macro_use] extern crate hagane_derive;
 
 mod allocator;
-// mod array;
+mod array;
 // mod attributed_string;
 // mod bag;
 // mod binary_heap;
@@ -17,15 +20,15 @@ mod allocator;
 // mod bundle;
 // mod byte_order;
 // mod calendar;
-// mod character_set;
+mod character_set;
 // mod data;
 // mod date;
 // mod date_formatter;
-// mod dictionary;
+mod dictionary;
 // mod error;
 // mod file_descriptor;
 // mod file_security;
-// mod locale;
+mod locale;
 // mod mach_port;
 // mod message_port;
 // mod notification_center;
@@ -33,6 +36,7 @@ mod null;
 // mod number;
 // mod number_formatter;
 mod object;
+mod pascal_string;
 // mod plug_in;
 // mod preferences;
 // mod property_list;
@@ -41,6 +45,7 @@ mod object;
 // mod socket;
 // mod stream;
 mod string;
+mod string_encoding;
 // mod string_tokenizer;
 // mod time_zone;
 // mod tree;
@@ -54,14 +59,39 @@ mod string;
 // mod xml_parser;
 
 pub use allocator::*;
+pub use array::*;
+pub use character_set::*;
+pub use dictionary::*;
+pub use locale::*;
 pub use null::*;
 pub use object::*;
+pub use pascal_string::*;
 pub use string::*;
+pub use string_encoding::*;
 
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::ffi::{CStr, CString, OsStr};
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
 use std::mem;
-use std::os::raw::c_void;
+use std::ops;
+use std::os::raw::{c_char, c_ulong, c_void};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::slice;
+use std::str;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{self, AtomicPtr};
 
-use hagane_core::Subtype;
+use backtrace::Backtrace;
+use hagane_core::{Downcast, Subtype};
 
 #[derive(Debug, Eq, PartialEq)]
 #[repr(u8)] pub enum Boolean {
@@ -112,35 +142,130 @@ pub type CFIndex = i64;
 
 #[repr(transparent)] pub struct CFPropertyListRef(usize);
 
-#[repr(i64)]
+#[repr(i64)] #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CFComparisonResult {
   kCFCompareLessThan = -1,
   kCFCompareEqualTo = 0,
   kCFCompareGreaterThan = 1
 }
 
+impl From<CFComparisonResult> for Ordering {
+  fn from(result: CFComparisonResult) -> Ordering {
+    return match result {
+      CFComparisonResult::kCFCompareLessThan => Ordering::Less,
+      CFComparisonResult::kCFCompareEqualTo => Ordering::Equal,
+      CFComparisonResult::kCFCompareGreaterThan => Ordering::Greater
+    };
+  }
+}
+
+impl From<Ordering> for CFComparisonResult {
+  fn from(ordering: Ordering) -> CFComparisonResult {
+    return match ordering {
+      Ordering::Less => CFComparisonResult::kCFCompareLessThan,
+      Ordering::Equal => CFComparisonResult::kCFCompareEqualTo,
+      Ordering::Greater => CFComparisonResult::kCFCompareGreaterThan
+    };
+  }
+}
+
 pub type CFComparatorFunction = unsafe extern fn(val1: *const c_void, val2: *const c_void, context: *mut c_void) -> CFComparisonResult;
 
 pub const kCFNotFound: CFIndex = -1;
 
+#[repr(C)] #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CFRange {
-  location: CFIndex,
-  length: CFIndex
+  pub location: CFIndex,
+  pub length: CFIndex
 }
 
 pub fn CFRangeMake(loc: CFIndex, len: CFIndex) -> CFRange {
   return CFRange { location: loc, length: len };
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum CFRangeError {
+  Reversed { start: usize, end: usize },
+  Negative(CFRange),
+  Overflow
+}
+
+impl fmt::Display for CFRangeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return match *self {
+      CFRangeError::Reversed { start, end } => write!(f, "range start {} is after its end {}", start, end),
+      CFRangeError::Negative(range) => write!(f, "range {:?} is negative", range),
+      CFRangeError::Overflow => write!(f, "range does not fit in the target type")
+    };
+  }
+}
+
+impl Error for CFRangeError {
+  fn description(&self) -> &str {
+    return match *self {
+      CFRangeError::Reversed { .. } => "range start is after its end",
+      CFRangeError::Negative(_) => "range is negative",
+      CFRangeError::Overflow => "range overflows"
+    };
+  }
+}
+
+impl TryFrom<ops::Range<usize>> for CFRange {
+  type Error = CFRangeError;
+
+  fn try_from(range: ops::Range<usize>) -> Result<CFRange, CFRangeError> {
+    if range.start > range.end {
+      return Err(CFRangeError::Reversed { start: range.start, end: range.end });
+    }
+
+    let location = CFIndex::try_from(range.start).map_err(|_| CFRangeError::Overflow)?;
+    let length = CFIndex::try_from(range.end - range.start).map_err(|_| CFRangeError::Overflow)?;
+
+    return Ok(CFRangeMake(location, length));
+  }
+}
+
+impl TryFrom<CFRange> for ops::Range<usize> {
+  type Error = CFRangeError;
+
+  fn try_from(range: CFRange) -> Result<ops::Range<usize>, CFRangeError> {
+    if range.location < 0 || range.length < 0 {
+      return Err(CFRangeError::Negative(range));
+    }
+
+    let end = range.location.checked_add(range.length).ok_or(CFRangeError::Overflow)?;
+    let start = usize::try_from(range.location).map_err(|_| CFRangeError::Overflow)?;
+    let end = usize::try_from(end).map_err(|_| CFRangeError::Overflow)?;
+
+    return Ok(start..end);
+  }
+}
+
 mod ext {
   use crate::*;
   
-  #[link(name = "CoreFoundation", kind = "framework")]
+  #[cfg_attr(any(target_os = "macos", target_os = "ios"), link(name = "CoreFoundation", kind = "framework"))]
   extern {
     pub fn CFCopyTypeIDDescription(type_id: CFTypeID) -> CFStringRef;
   }
 }
 
-pub fn CFCopyTypeIDDescription(type_id: CFTypeID) -> CFStringRef {
-  return unsafe { ext::CFCopyTypeIDDescription(type_id) };
+pub fn CFCopyTypeIDDescription(type_id: CFTypeID) -> CFType<CFStringRef> {
+  return unsafe { CFType::from_create_rule(ext::CFCopyTypeIDDescription(type_id)) };
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn it_converts_ranges() {
+    assert_eq!(CFRange::try_from(2..5), Ok(CFRangeMake(2, 3)));
+    assert_eq!(CFRange::try_from(5..2), Err(CFRangeError::Reversed { start: 5, end: 2 }));
+    assert_eq!(CFRange::try_from(usize::max_value() - 1..usize::max_value()), Err(CFRangeError::Overflow));
+
+    assert_eq!(ops::Range::try_from(CFRangeMake(2, 3)), Ok(2..5));
+    assert_eq!(ops::Range::try_from(CFRangeMake(-1, 3)), Err(CFRangeError::Negative(CFRangeMake(-1, 3))));
+    assert_eq!(ops::Range::try_from(CFRangeMake(CFIndex::max_value(), 1)), Err(CFRangeError::Overflow));
+  }
 }
@@ -0,0 +1,94 @@
+use crate::*;
+
+mod ext {
+  use crate::*;
+
+  extern {
+    pub fn CFLocaleGetTypeID() -> CFTypeID;
+
+    pub fn CFLocaleGetSystem() -> CFLocaleRef;
+    pub fn CFLocaleCopyCurrent() -> CFLocaleRef;
+    // CFArrayRef CFLocaleCopyAvailableLocaleIdentifiers(void);
+    // CFArrayRef CFLocaleCopyISOLanguageCodes(void);
+    // CFArrayRef CFLocaleCopyISOCountryCodes(void);
+    // CFArrayRef CFLocaleCopyISOCurrencyCodes(void);
+    // CFArrayRef CFLocaleCopyCommonISOCurrencyCodes(void);
+    // CFArrayRef CFLocaleCopyPreferredLanguages(void);
+    pub fn CFLocaleCreateCanonicalLocaleIdentifierFromString(allocator: CFAllocatorRef, localeIdentifier: CFStringRef) -> CFStringRef;
+    // CFDictionaryRef CFLocaleCreateComponentsFromLocaleIdentifier(CFAllocatorRef allocator, CFLocaleIdentifier localeID);
+    // CFLocaleIdentifier CFLocaleCreateLocaleIdentifierFromComponents(CFAllocatorRef allocator, CFDictionaryRef dictionary);
+    pub fn CFLocaleCreate(allocator: CFAllocatorRef, localeIdentifier: CFStringRef) -> CFLocaleRef;
+    pub fn CFLocaleCreateCopy(allocator: CFAllocatorRef, locale: CFLocaleRef) -> CFLocaleRef;
+    pub fn CFLocaleGetIdentifier(locale: CFLocaleRef) -> CFStringRef;
+    // CFTypeRef CFLocaleGetValue(CFLocaleRef locale, CFLocaleKey key);
+    // CFStringRef CFLocaleCopyDisplayNameForPropertyValue(CFLocaleRef displayLocale, CFLocaleKey key, CFStringRef value);
+  }
+}
+
+#[cf_type(parent = CFTypeRef, type_id = CFLocaleGetTypeID, owned = CFLocale)]
+pub struct CFLocaleRef;
+
+pub fn CFLocaleGetTypeID() -> CFTypeID {
+  return unsafe { ext::CFLocaleGetTypeID() };
+}
+
+pub fn CFLocaleGetSystem() -> CFRef<'static, CFLocaleRef> {
+  return unsafe { CFRef::from_get_rule(ext::CFLocaleGetSystem()) };
+}
+
+pub fn CFLocaleCopyCurrent() -> CFLocale {
+  return unsafe { CFType::from_create_rule(ext::CFLocaleCopyCurrent()) };
+}
+
+pub fn CFLocaleCreateCanonicalLocaleIdentifierFromString<A: Subtype<CFAllocatorRef>, S: Subtype<CFStringRef>>(allocator: &A, localeIdentifier: &S) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFLocaleCreateCanonicalLocaleIdentifierFromString(allocator.upcast(), localeIdentifier.upcast())) };
+}
+
+pub fn CFLocaleCreate<A: Subtype<CFAllocatorRef>, S: Subtype<CFStringRef>>(allocator: &A, localeIdentifier: &S) -> Option<CFLocale> {
+  return unsafe { CFType::try_from_create_rule(ext::CFLocaleCreate(allocator.upcast(), localeIdentifier.upcast())) };
+}
+
+pub fn CFLocaleCreateCopy<A: Subtype<CFAllocatorRef>, L: Subtype<CFLocaleRef>>(allocator: &A, locale: &L) -> Option<CFLocale> {
+  return unsafe { CFType::try_from_create_rule(ext::CFLocaleCreateCopy(allocator.upcast(), locale.upcast())) };
+}
+
+pub fn CFLocaleGetIdentifier<'a, L: Subtype<CFLocaleRef>>(locale: &'a L) -> CFRef<'a, CFStringRef> {
+  return unsafe { CFRef::from_get_rule(ext::CFLocaleGetIdentifier(locale.upcast())) };
+}
+
+impl CFLocale {
+  pub fn current() -> CFLocale {
+    return CFLocaleCopyCurrent();
+  }
+
+  /// Creates a locale from an identifier such as `"sv_SE"` or `"en-US"`.
+  pub fn from_identifier(identifier: &str) -> CFLocale {
+    return CFLocaleCreate(kCFAllocatorDefault, &CFString::from_str(identifier)).expect("could not allocate CFLocale");
+  }
+}
+
+pub trait CFLocaleClass : Subtype<CFLocaleRef> {
+  fn get_identifier<'a>(&'a self) -> CFRef<'a, CFStringRef> {
+    return CFLocaleGetIdentifier(self);
+  }
+
+  fn is_hyphenation_available(&self) -> bool {
+    return CFStringIsHyphenationAvailableForLocale(self) == Boolean::TRUE;
+  }
+}
+
+impl<T> CFLocaleClass for T where T: Subtype<CFLocaleRef> { }
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn it_identifies() {
+    assert_eq!(CFLocale::from_identifier("sv_SE").get_identifier().to_string(), "sv_SE");
+    assert_eq!(CFLocaleGetSystem().get_identifier().to_string(), "");
+
+    let canonical = CFLocaleCreateCanonicalLocaleIdentifierFromString(kCFAllocatorDefault, &CFString::from_str("en-US")).unwrap();
+    assert_eq!(canonical.to_string(), "en_US");
+  }
+}
@@ -6,30 +6,19 @@ mod ext {
   extern {
     pub fn CFNullGetTypeID() -> CFTypeID;
 
-    pub static kCFNull: CFNullRef;
+    pub static kCFNull: CFRef<'static, CFNullRef>;
   }
 }
 
-#[repr(transparent)] pub struct CFNullRef(usize);
-
-unsafe impl Subtype<CFTypeRef> for CFNullRef {
-  unsafe fn upcast(&self) -> CFTypeRef {
-    return CFTypeRef(self.0);
-  }
-}
-
-unsafe impl Subtype<CFNullRef> for CFNullRef {
-  unsafe fn upcast(&self) -> CFNullRef {
-    return CFNullRef(self.0);
-  }
-}
+#[cf_type(parent = CFTypeRef, type_id = CFNullGetTypeID, owned = CFNull)]
+pub struct CFNullRef;
 
 
 pub fn CFNullGetTypeID() -> CFTypeID {
   return unsafe { ext::CFNullGetTypeID() };
 }
 
-pub static kCFNull: &'static CFNullRef = unsafe { &ext::kCFNull };
+pub static kCFNull: &'static CFRef<'static, CFNullRef> = unsafe { &ext::kCFNull };
 
 #[cfg(test)]
 mod tests {
@@ -26,6 +26,134 @@ unsafe impl Subtype<CFTypeRef> for CFTypeRef {
   }
 }
 
+/// An owned reference to a Core Foundation object, released when dropped.
+#[repr(transparent)] pub struct CFType<T: Subtype<CFTypeRef>>(T);
+
+impl<T: Subtype<CFTypeRef>> CFType<T> {
+  /// Takes ownership of an object returned by a Create or Copy function.
+  pub unsafe fn from_create_rule(cf: T) -> CFType<T> {
+    return CFType(cf);
+  }
+
+  /// Like `from_create_rule`, but returns `None` if `cf` is `NULL`.
+  pub unsafe fn try_from_create_rule(cf: T) -> Option<CFType<T>> {
+    if cf.upcast().0 == 0 {
+      return None;
+    }
+
+    return Some(CFType(cf));
+  }
+
+  /// Retains an object returned by a Get function, taking ownership of the new reference.
+  pub unsafe fn from_get_rule(cf: T) -> CFType<T> {
+    return CFType(CFRetain(cf));
+  }
+
+  /// Gives up ownership without releasing, leaving the caller responsible for the reference.
+  pub fn into_raw(self) -> T {
+    let cf = unsafe { ptr::read(&self.0) };
+    mem::forget(self);
+
+    return cf;
+  }
+
+  /// Converts to a more specific type, handing the object back along with the error if its type ID does not match.
+  pub fn downcast<U>(self) -> Result<CFType<U>, (CFType<T>, TypeMismatch)> where U: Downcast<CFTypeRef, TypeID = CFTypeID> {
+    return match downcast(&self) {
+      Ok(cf) => {
+        mem::forget(self);
+        Ok(CFType(cf))
+      },
+      Err(error) => Err((self, error))
+    };
+  }
+
+  pub fn downcast_ref<'a, U>(&'a self) -> Option<CFRef<'a, U>> where U: Downcast<CFTypeRef, TypeID = CFTypeID> {
+    return downcast(self).ok().map(|cf| CFRef(cf, PhantomData));
+  }
+}
+
+unsafe impl<T, U> Subtype<U> for CFType<T> where T: Subtype<CFTypeRef> + Subtype<U> {
+  unsafe fn upcast(&self) -> U {
+    return self.0.upcast();
+  }
+}
+
+impl Clone for CFType<CFTypeRef> {
+  fn clone(&self) -> CFType<CFTypeRef> {
+    return unsafe { CFType::from_get_rule(self.0.upcast()) };
+  }
+}
+
+impl<T: Subtype<CFTypeRef>> Drop for CFType<T> {
+  fn drop(&mut self) {
+    unsafe { ext::CFRelease(self.0.upcast()) };
+  }
+}
+
+/// A borrowed reference to a Core Foundation object that is owned elsewhere and valid for `'a`.
+#[repr(transparent)] pub struct CFRef<'a, T: Subtype<CFTypeRef> + 'a>(T, PhantomData<&'a T>);
+
+impl<'a, T: Subtype<CFTypeRef>> CFRef<'a, T> {
+  /// Borrows an object returned by a Get function without retaining it.
+  pub unsafe fn from_get_rule(cf: T) -> CFRef<'a, T> {
+    return CFRef(cf, PhantomData);
+  }
+
+  /// Like `from_get_rule`, but returns `None` if `cf` is `NULL`.
+  pub unsafe fn try_from_get_rule(cf: T) -> Option<CFRef<'a, T>> {
+    if cf.upcast().0 == 0 {
+      return None;
+    }
+
+    return Some(CFRef(cf, PhantomData));
+  }
+
+  /// Retains the object, returning an owned reference that may outlive `'a`.
+  pub fn to_owned(&self) -> CFType<T> where T: Subtype<T> {
+    return unsafe { CFType::from_get_rule(self.0.upcast()) };
+  }
+
+  pub fn downcast<U>(&self) -> Result<CFRef<'a, U>, TypeMismatch> where U: Downcast<CFTypeRef, TypeID = CFTypeID> {
+    return downcast(self).map(|cf| CFRef(cf, PhantomData));
+  }
+}
+
+unsafe impl<'a, T, U> Subtype<U> for CFRef<'a, T> where T: Subtype<CFTypeRef> + Subtype<U> {
+  unsafe fn upcast(&self) -> U {
+    return self.0.upcast();
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TypeMismatch {
+  pub expected: CFTypeID,
+  pub found: CFTypeID
+}
+
+impl fmt::Display for TypeMismatch {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return write!(f, "expected CFTypeID {}, found {}", self.expected.0, self.found.0);
+  }
+}
+
+impl Error for TypeMismatch {
+  fn description(&self) -> &str {
+    return "Core Foundation type mismatch";
+  }
+}
+
+fn downcast<T: Subtype<CFTypeRef>, U: Downcast<CFTypeRef, TypeID = CFTypeID>>(cf: &T) -> Result<U, TypeMismatch> {
+  let expected = U::type_id();
+  let found = CFGetTypeID(cf);
+
+  if expected != found {
+    return Err(TypeMismatch { expected: expected, found: found });
+  }
+
+  return Ok(unsafe { U::downcast_unchecked(&cf.upcast()) });
+}
+
 pub fn CFGetTypeID<T: Subtype<CFTypeRef>>(cf: &T) -> CFTypeID {
   return unsafe { ext::CFGetTypeID(cf.upcast()) };
 }
@@ -36,8 +164,8 @@ pub unsafe fn CFRetain<T: Subtype<CFTypeRef>>(cf: T) -> T {
   return cf;
 }
 
-pub fn CFRelease<T: Subtype<CFTypeRef>>(cf: T) {
-  unsafe { ext::CFRelease(cf.upcast()) };
+pub unsafe fn CFRelease<T: Subtype<CFTypeRef>>(cf: T) {
+  ext::CFRelease(cf.upcast());
 }
 
 pub unsafe fn CFAutorelease<T: Subtype<CFTypeRef>>(cf: T) -> T {
@@ -58,12 +186,12 @@ pub fn CFHash<T: Subtype<CFTypeRef>>(cf: &T) -> CFHashCode {
   return unsafe { ext::CFHash(cf.upcast()) };
 }
 
-pub fn CFCopyDescription<T: Subtype<CFTypeRef>>(cf: &T) -> CFStringRef {
-  return unsafe { ext::CFCopyDescription(cf.upcast()) };
+pub fn CFCopyDescription<T: Subtype<CFTypeRef>>(cf: &T) -> CFType<CFStringRef> {
+  return unsafe { CFType::from_create_rule(ext::CFCopyDescription(cf.upcast())) };
 }
 
-pub fn CFGetAllocator<T: Subtype<CFTypeRef>>(cf: &T) -> CFAllocatorRef {
-  return unsafe { ext::CFGetAllocator(cf.upcast()).retain() };
+pub fn CFGetAllocator<'a, T: Subtype<CFTypeRef>>(cf: &'a T) -> CFRef<'a, CFAllocatorRef> {
+  return unsafe { CFRef::from_get_rule(ext::CFGetAllocator(cf.upcast())) };
 }
 
 pub fn CFShow<T: Subtype<CFTypeRef>>(cf: &T) {
@@ -79,7 +207,7 @@ pub trait CFTypeClass : Subtype<CFTypeRef> {
     return CFRetain(self);
   }
 
-  fn release(self) {
+  unsafe fn release(self) {
     CFRelease(self);
   }
 
@@ -99,11 +227,11 @@ pub trait CFTypeClass : Subtype<CFTypeRef> {
     return CFHash(self);
   }
 
-  fn copy_description(&self) -> CFStringRef {
+  fn copy_description(&self) -> CFType<CFStringRef> {
     return CFCopyDescription(self);
   }
 
-  fn get_allocator(&self) -> CFAllocatorRef {
+  fn get_allocator<'a>(&'a self) -> CFRef<'a, CFAllocatorRef> {
     return CFGetAllocator(self);
   }
 
@@ -122,5 +250,45 @@ mod tests {
   fn it_compares() {
     assert_eq!(CFEqual(kCFNull, kCFAllocatorSystemDefault), Boolean::FALSE);
   }
+
+  #[test]
+  fn it_releases() {
+    let description = CFCopyDescription(kCFNull);
+    let count = description.get_retain_count();
+
+    let copy = description.clone();
+    assert_eq!(copy.get_retain_count(), count + 1);
+
+    drop(copy);
+    assert_eq!(description.get_retain_count(), count);
+  }
+
+  #[test]
+  fn it_borrows() {
+    let description = CFCopyDescription(kCFNull);
+    let count = description.get_retain_count();
+
+    let borrowed = unsafe { CFRef::<CFStringRef>::from_get_rule(description.upcast()) };
+    assert_eq!(borrowed.get_retain_count(), count);
+
+    let owned = borrowed.to_owned();
+    assert_eq!(description.get_retain_count(), count + 1);
+
+    drop(owned);
+    assert_eq!(description.get_retain_count(), count);
+  }
+
+  #[test]
+  fn it_downcasts() {
+    let object = unsafe { CFType::<CFTypeRef>::from_create_rule(CFCopyDescription(kCFNull).into_raw().upcast()) };
+
+    assert!(object.downcast_ref::<CFStringRef>().is_some());
+    assert!(object.downcast_ref::<CFNullRef>().is_none());
+    assert!(kCFNull.downcast::<CFNullRef>().is_ok());
+
+    let (object, error) = object.downcast::<CFAllocatorRef>().err().unwrap();
+    assert_eq!(error, TypeMismatch { expected: CFAllocatorGetTypeID(), found: CFStringGetTypeID() });
+    assert!(object.downcast::<CFStringRef>().ok().unwrap().to_string().starts_with("<CFNull"));
+  }
 }
 
@@ -0,0 +1,187 @@
+use crate::*;
+
+/// A borrowed Pascal string: a length byte followed by that many bytes of text, like `ConstStr255Param`.
+pub struct PascalStr {
+  bytes: [u8]
+}
+
+/// An owned Pascal string in a `Str255` buffer.
+#[derive(Clone, Copy)]
+pub struct PascalString {
+  buffer: Str255
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PascalStrError {
+  /// The slice is empty, so it has no length byte.
+  MissingLength,
+  /// The length byte does not match the number of bytes that follow it.
+  LengthMismatch { declared: usize, actual: usize },
+  /// The text is longer than the 255 bytes a length byte can count.
+  TooLong(usize)
+}
+
+impl fmt::Display for PascalStrError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return match *self {
+      PascalStrError::MissingLength => f.write_str("Pascal string is missing its length byte"),
+      PascalStrError::LengthMismatch { declared, actual } => write!(f, "Pascal string declares {} bytes but has {}", declared, actual),
+      PascalStrError::TooLong(length) => write!(f, "{} bytes is too long for a Pascal string", length)
+    };
+  }
+}
+
+impl Error for PascalStrError {
+  fn description(&self) -> &str {
+    return "invalid Pascal string";
+  }
+}
+
+impl PascalStr {
+  /// Wraps a length byte and exactly that many bytes of text.
+  pub fn from_bytes_with_len(bytes: &[u8]) -> Result<&PascalStr, PascalStrError> {
+    let declared = *bytes.first().ok_or(PascalStrError::MissingLength)? as usize;
+
+    if declared != bytes.len() - 1 {
+      return Err(PascalStrError::LengthMismatch { declared: declared, actual: bytes.len() - 1 });
+    }
+
+    return Ok(unsafe { PascalStr::from_bytes_with_len_unchecked(bytes) });
+  }
+
+  /// Like `from_bytes_with_len`, but ignores anything after the text, as in a partially used `Str255`.
+  pub fn from_buffer(buffer: &[u8]) -> Result<&PascalStr, PascalStrError> {
+    let declared = *buffer.first().ok_or(PascalStrError::MissingLength)? as usize;
+
+    if declared > buffer.len() - 1 {
+      return Err(PascalStrError::LengthMismatch { declared: declared, actual: buffer.len() - 1 });
+    }
+
+    return Ok(unsafe { PascalStr::from_bytes_with_len_unchecked(&buffer[..declared + 1]) });
+  }
+
+  pub unsafe fn from_bytes_with_len_unchecked(bytes: &[u8]) -> &PascalStr {
+    return &*(bytes as *const [u8] as *const PascalStr);
+  }
+
+  /// Reads the length byte at `ptr` to find the end of the string.
+  pub unsafe fn from_ptr<'a>(ptr: ConstStringPtr) -> &'a PascalStr {
+    return PascalStr::from_bytes_with_len_unchecked(slice::from_raw_parts(ptr, *ptr as usize + 1));
+  }
+
+  pub fn as_ptr(&self) -> ConstStringPtr {
+    return self.bytes.as_ptr();
+  }
+
+  pub fn len(&self) -> usize {
+    return self.bytes.len() - 1;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.len() == 0;
+  }
+
+  pub fn to_bytes(&self) -> &[u8] {
+    return &self.bytes[1..];
+  }
+
+  pub fn to_bytes_with_len(&self) -> &[u8] {
+    return &self.bytes;
+  }
+}
+
+impl fmt::Debug for PascalStr {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return write!(f, "PascalStr({:?})", String::from_utf8_lossy(self.to_bytes()));
+  }
+}
+
+impl PartialEq for PascalStr {
+  fn eq(&self, other: &PascalStr) -> bool {
+    return self.bytes == other.bytes;
+  }
+}
+
+impl Eq for PascalStr { }
+
+impl PascalString {
+  pub fn new(bytes: &[u8]) -> Result<PascalString, PascalStrError> {
+    if bytes.len() > 255 {
+      return Err(PascalStrError::TooLong(bytes.len()));
+    }
+
+    let mut buffer = [0; 256];
+    buffer[0] = bytes.len() as u8;
+    buffer[1..bytes.len() + 1].copy_from_slice(bytes);
+
+    return Ok(PascalString { buffer: buffer });
+  }
+
+  /// Takes a `Str255` filled in by a C function; any length byte fits in the buffer.
+  pub fn from_str255(buffer: Str255) -> PascalString {
+    return PascalString { buffer: buffer };
+  }
+
+  pub fn as_pascal_str(&self) -> &PascalStr {
+    return unsafe { PascalStr::from_bytes_with_len_unchecked(&self.buffer[..self.buffer[0] as usize + 1]) };
+  }
+
+  pub fn into_str255(self) -> Str255 {
+    return self.buffer;
+  }
+}
+
+impl ops::Deref for PascalString {
+  type Target = PascalStr;
+
+  fn deref(&self) -> &PascalStr {
+    return self.as_pascal_str();
+  }
+}
+
+impl fmt::Debug for PascalString {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return fmt::Debug::fmt(self.as_pascal_str(), f);
+  }
+}
+
+impl PartialEq for PascalString {
+  fn eq(&self, other: &PascalString) -> bool {
+    return self.as_pascal_str() == other.as_pascal_str();
+  }
+}
+
+impl Eq for PascalString { }
+
+impl<'a> TryFrom<&'a [u8]> for PascalString {
+  type Error = PascalStrError;
+
+  fn try_from(bytes: &'a [u8]) -> Result<PascalString, PascalStrError> {
+    return PascalString::new(bytes);
+  }
+}
+
+impl<'a> From<&'a PascalStr> for PascalString {
+  fn from(string: &'a PascalStr) -> PascalString {
+    return PascalString::new(string.to_bytes()).unwrap();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+
+  #[test]
+  fn it_validates_lengths() {
+    assert_eq!(PascalStr::from_bytes_with_len(b"\x03abc").unwrap().to_bytes(), b"abc");
+    assert_eq!(PascalStr::from_bytes_with_len(b"\x04abc"), Err(PascalStrError::LengthMismatch { declared: 4, actual: 3 }));
+    assert_eq!(PascalStr::from_bytes_with_len(b""), Err(PascalStrError::MissingLength));
+    assert_eq!(PascalStr::from_buffer(b"\x02abc").unwrap().to_bytes_with_len(), b"\x02ab");
+
+    let string = PascalString::new(b"hagane").unwrap();
+    assert_eq!(string.len(), 6);
+    assert_eq!(&string.into_str255()[..7], b"\x06hagane");
+    assert_eq!(PascalString::new(&[b'a'; 256]), Err(PascalStrError::TooLong(256)));
+    assert_eq!(PascalString::try_from(&b""[..]).unwrap().to_bytes_with_len(), b"\x00");
+  }
+}
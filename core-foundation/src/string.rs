@@ -1,6 +1,24 @@
 use crate::*;
 
-#[repr(u32)] enum CFStringEncoding {
+#[repr(transparent)] #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CFStringEncoding(pub u32);
+
+macro_rules! string_encodings {
+  ($($name:ident = $value:tt),*) => {
+    impl CFStringEncoding {
+      $(pub const $name: CFStringEncoding = CFStringEncoding($value);)*
+
+      fn constant_name(self) -> Option<&'static str> {
+        return match self.0 {
+          $($value => Some(stringify!($name)),)*
+          _ => None
+        };
+      }
+    }
+  }
+}
+
+string_encodings! {
   kCFStringEncodingMacRoman = 0,
   kCFStringEncodingMacJapanese = 1,
   kCFStringEncodingMacChineseTrad = 2,
@@ -51,7 +69,6 @@ use crate::*;
 
   kCFStringEncodingMacHFS = 0xFF,
 
-  // Alias for UTF16, kCFStringEncodingUnicode = 0x0100,
   kCFStringEncodingUTF16 = 0x0100,
   kCFStringEncodingUTF16BE = 0x10000100,
   kCFStringEncodingUTF16LE = 0x14000100,
@@ -163,20 +180,187 @@ use crate::*;
   kCFStringEncodingUTF32LE = 0x1c000100
 }
 
-// Should be a bitfield
-// #[repr(u64)] enum CFStringCompareFlags {
-//   kCFCompareCaseInsensitive = 1,	
-//   kCFCompareBackwards = 4,
-//   kCFCompareAnchored = 8,
-//   kCFCompareNonliteral = 16,
-//   kCFCompareLocalized = 32,
-//   kCFCompareNumerically = 64,
-//   kCFCompareDiacriticInsensitive = 128,
-//   kCFCompareWidthInsensitive = 256,
-//   kCFCompareForcedOrdering = 512
-// }
-
-#[repr(u64)] enum CFStringNormalizationForm {
+impl CFStringEncoding {
+  pub const kCFStringEncodingUnicode: CFStringEncoding = CFStringEncoding::kCFStringEncodingUTF16;
+  pub const kCFStringEncodingInvalidId: CFStringEncoding = CFStringEncoding(0xFFFFFFFF);
+
+  pub fn system() -> CFStringEncoding {
+    return CFStringGetSystemEncoding();
+  }
+
+  pub fn available() -> &'static [CFStringEncoding] {
+    return CFStringGetListOfAvailableEncodings();
+  }
+
+  pub fn is_available(self) -> bool {
+    return CFStringIsEncodingAvailable(self) == Boolean::TRUE;
+  }
+
+  pub fn name(self) -> Option<CFRef<'static, CFStringRef>> {
+    return CFStringGetNameOfEncoding(self);
+  }
+
+  /// Looks up an encoding by IANA character set name, as found in HTTP and MIME `charset` parameters.
+  pub fn from_iana_charset_name(name: &str) -> Option<CFStringEncoding> {
+    return valid(CFStringConvertIANACharSetNameToEncoding(&CFString::from_str(name)));
+  }
+
+  pub fn iana_charset_name(self) -> Option<CFRef<'static, CFStringRef>> {
+    return CFStringConvertEncodingToIANACharSetName(self);
+  }
+
+  pub fn from_windows_codepage(codepage: u32) -> Option<CFStringEncoding> {
+    return valid(CFStringConvertWindowsCodepageToEncoding(codepage));
+  }
+
+  pub fn windows_codepage(self) -> Option<u32> {
+    return valid(CFStringEncoding(CFStringConvertEncodingToWindowsCodepage(self))).map(|encoding| encoding.0);
+  }
+
+  pub fn from_ns_string_encoding(encoding: c_ulong) -> Option<CFStringEncoding> {
+    return valid(CFStringConvertNSStringEncodingToEncoding(encoding));
+  }
+
+  pub fn ns_string_encoding(self) -> Option<c_ulong> {
+    let encoding = CFStringConvertEncodingToNSStringEncoding(self);
+
+    if encoding == CFStringEncoding::kCFStringEncodingInvalidId.0 as c_ulong {
+      return None;
+    }
+
+    return Some(encoding);
+  }
+
+  pub fn most_compatible_mac_encoding(self) -> Option<CFStringEncoding> {
+    return valid(CFStringGetMostCompatibleMacStringEncoding(self));
+  }
+}
+
+fn valid(encoding: CFStringEncoding) -> Option<CFStringEncoding> {
+  if encoding == CFStringEncoding::kCFStringEncodingInvalidId {
+    return None;
+  }
+
+  return Some(encoding);
+}
+
+impl fmt::Debug for CFStringEncoding {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return match self.constant_name() {
+      Some(name) => f.write_str(name),
+      None => write!(f, "CFStringEncoding({:#X})", self.0)
+    };
+  }
+}
+
+#[repr(transparent)] #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CFStringCompareFlags(pub u64);
+
+macro_rules! compare_flags {
+  ($($name:ident = $value:expr),*) => {
+    impl CFStringCompareFlags {
+      $(pub const $name: CFStringCompareFlags = CFStringCompareFlags($value);)*
+
+      const NAMES: &'static [(&'static str, u64)] = &[$((stringify!($name), $value)),*];
+    }
+  }
+}
+
+compare_flags! {
+  kCFCompareCaseInsensitive = 1,
+  kCFCompareBackwards = 4,
+  kCFCompareAnchored = 8,
+  kCFCompareNonliteral = 16,
+  kCFCompareLocalized = 32,
+  kCFCompareNumerically = 64,
+  kCFCompareDiacriticInsensitive = 128,
+  kCFCompareWidthInsensitive = 256,
+  kCFCompareForcedOrdering = 512
+}
+
+impl CFStringCompareFlags {
+  pub fn empty() -> CFStringCompareFlags {
+    return CFStringCompareFlags(0);
+  }
+
+  pub fn is_empty(self) -> bool {
+    return self.0 == 0;
+  }
+
+  pub fn contains(self, other: CFStringCompareFlags) -> bool {
+    return self.0 & other.0 == other.0;
+  }
+
+  pub fn insert(&mut self, other: CFStringCompareFlags) {
+    self.0 |= other.0;
+  }
+
+  pub fn remove(&mut self, other: CFStringCompareFlags) {
+    self.0 &= !other.0;
+  }
+}
+
+impl ops::BitOr for CFStringCompareFlags {
+  type Output = CFStringCompareFlags;
+
+  fn bitor(self, other: CFStringCompareFlags) -> CFStringCompareFlags {
+    return CFStringCompareFlags(self.0 | other.0);
+  }
+}
+
+impl ops::BitOrAssign for CFStringCompareFlags {
+  fn bitor_assign(&mut self, other: CFStringCompareFlags) {
+    self.0 |= other.0;
+  }
+}
+
+impl ops::BitAnd for CFStringCompareFlags {
+  type Output = CFStringCompareFlags;
+
+  fn bitand(self, other: CFStringCompareFlags) -> CFStringCompareFlags {
+    return CFStringCompareFlags(self.0 & other.0);
+  }
+}
+
+impl ops::BitAndAssign for CFStringCompareFlags {
+  fn bitand_assign(&mut self, other: CFStringCompareFlags) {
+    self.0 &= other.0;
+  }
+}
+
+impl ops::Sub for CFStringCompareFlags {
+  type Output = CFStringCompareFlags;
+
+  fn sub(self, other: CFStringCompareFlags) -> CFStringCompareFlags {
+    return CFStringCompareFlags(self.0 & !other.0);
+  }
+}
+
+impl fmt::Debug for CFStringCompareFlags {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut remaining = self.0;
+    let mut first = true;
+
+    for &(name, value) in CFStringCompareFlags::NAMES {
+      if remaining & value == value {
+        f.write_str(if first { "" } else { " | " })?;
+        f.write_str(name)?;
+        remaining &= !value;
+        first = false;
+      }
+    }
+
+    if remaining != 0 || first {
+      f.write_str(if first { "" } else { " | " })?;
+      write!(f, "{:#X}", remaining)?;
+    }
+
+    return Ok(());
+  }
+}
+
+#[repr(u64)] #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CFStringNormalizationForm {
   kCFStringNormalizationFormD = 0,
   kCFStringNormalizationFormKD = 1,
   kCFStringNormalizationFormC = 2,
@@ -190,147 +374,1753 @@ mod ext {
   extern {
     pub fn CFStringGetTypeID() -> CFTypeID;
 
-    // CFStringRef CFStringCreateWithPascalString(CFAllocatorRef alloc, ConstStr255Param pStr, CFStringEncoding encoding);
-    // CFStringRef CFStringCreateWithCString(CFAllocatorRef alloc, const char *cStr, CFStringEncoding encoding);
-    // CFStringRef CFStringCreateWithBytes(CFAllocatorRef alloc, const UInt8 *bytes, CFIndex numBytes, CFStringEncoding encoding, Boolean isExternalRepresentation);
-    // CFStringRef CFStringCreateWithCharacters(CFAllocatorRef alloc, const UniChar *chars, CFIndex numChars);
+    pub fn CFStringCreateWithPascalString(alloc: CFAllocatorRef, pStr: ConstStr255Param, encoding: CFStringEncoding) -> CFStringRef;
+    pub fn CFStringCreateWithCString(alloc: CFAllocatorRef, cStr: *const c_char, encoding: CFStringEncoding) -> CFStringRef;
+    pub fn CFStringCreateWithBytes(alloc: CFAllocatorRef, bytes: *const UInt8, numBytes: CFIndex, encoding: CFStringEncoding, isExternalRepresentation: Boolean) -> CFStringRef;
+    pub fn CFStringCreateWithCharacters(alloc: CFAllocatorRef, chars: *const UniChar, numChars: CFIndex) -> CFStringRef;
     // CFStringRef CFStringCreateWithPascalStringNoCopy(CFAllocatorRef alloc, ConstStr255Param pStr, CFStringEncoding encoding, CFAllocatorRef contentsDeallocator);
     // CFStringRef CFStringCreateWithCStringNoCopy(CFAllocatorRef alloc, const char *cStr, CFStringEncoding encoding, CFAllocatorRef contentsDeallocator);
-    // CFStringRef CFStringCreateWithBytesNoCopy(CFAllocatorRef alloc, const UInt8 *bytes, CFIndex numBytes, CFStringEncoding encoding, Boolean isExternalRepresentation, CFAllocatorRef contentsDeallocator);
-    // CFStringRef CFStringCreateWithCharactersNoCopy(CFAllocatorRef alloc, const UniChar *chars, CFIndex numChars, CFAllocatorRef contentsDeallocator);
-    // CFStringRef CFStringCreateWithSubstring(CFAllocatorRef alloc, CFStringRef str, CFRange range);
-    // CFStringRef CFStringCreateCopy(CFAllocatorRef alloc, CFStringRef theString);
-    // CFStringRef CFStringCreateWithFormat(CFAllocatorRef alloc, CFDictionaryRef formatOptions, CFStringRef format, ...) CF_FORMAT_FUNCTION(3,4);
+    pub fn CFStringCreateWithBytesNoCopy(alloc: CFAllocatorRef, bytes: *const UInt8, numBytes: CFIndex, encoding: CFStringEncoding, isExternalRepresentation: Boolean, contentsDeallocator: CFAllocatorRef) -> CFStringRef;
+    pub fn CFStringCreateWithCharactersNoCopy(alloc: CFAllocatorRef, chars: *const UniChar, numChars: CFIndex, contentsDeallocator: CFAllocatorRef) -> CFStringRef;
+    pub fn CFStringCreateWithSubstring(alloc: CFAllocatorRef, str: CFStringRef, range: CFRange) -> CFStringRef;
+    pub fn CFStringCreateCopy(alloc: CFAllocatorRef, theString: CFStringRef) -> CFStringRef;
+    // formatOptions is a CFDictionaryRef, or a CFLocaleRef for localized formatting.
+    pub fn CFStringCreateWithFormat(alloc: CFAllocatorRef, formatOptions: CFTypeRef, format: CFStringRef, ...) -> CFStringRef;
     // CFStringRef CFStringCreateWithFormatAndArguments(CFAllocatorRef alloc, CFDictionaryRef formatOptions, CFStringRef format, va_list arguments) CF_FORMAT_FUNCTION(3,0);
-    // CFMutableStringRef CFStringCreateMutable(CFAllocatorRef alloc, CFIndex maxLength);
-    // CFMutableStringRef CFStringCreateMutableCopy(CFAllocatorRef alloc, CFIndex maxLength, CFStringRef theString);
-    // CFMutableStringRef CFStringCreateMutableWithExternalCharactersNoCopy(CFAllocatorRef alloc, UniChar *chars, CFIndex numChars, CFIndex capacity, CFAllocatorRef externalCharactersAllocator);
+    pub fn CFStringCreateMutable(alloc: CFAllocatorRef, maxLength: CFIndex) -> CFMutableStringRef;
+    pub fn CFStringCreateMutableCopy(alloc: CFAllocatorRef, maxLength: CFIndex, theString: CFStringRef) -> CFMutableStringRef;
+    pub fn CFStringCreateMutableWithExternalCharactersNoCopy(alloc: CFAllocatorRef, chars: *mut UniChar, numChars: CFIndex, capacity: CFIndex, externalCharactersAllocator: CFAllocatorRef) -> CFMutableStringRef;
     // 
-    // CFIndex CFStringGetLength(CFStringRef theString);
+    pub fn CFStringGetLength(theString: CFStringRef) -> CFIndex;
     // UniChar CFStringGetCharacterAtIndex(CFStringRef theString, CFIndex idx);
-    // void CFStringGetCharacters(CFStringRef theString, CFRange range, UniChar *buffer);
-    // Boolean CFStringGetPascalString(CFStringRef theString, StringPtr buffer, CFIndex bufferSize, CFStringEncoding encoding);
+    pub fn CFStringGetCharacters(theString: CFStringRef, range: CFRange, buffer: *mut UniChar);
+    pub fn CFStringGetPascalString(theString: CFStringRef, buffer: StringPtr, bufferSize: CFIndex, encoding: CFStringEncoding) -> Boolean;
     // Boolean CFStringGetCString(CFStringRef theString, char *buffer, CFIndex bufferSize, CFStringEncoding encoding);
-    // ConstStringPtr CFStringGetPascalStringPtr(CFStringRef theString, CFStringEncoding encoding);
-    // const char *CFStringGetCStringPtr(CFStringRef theString, CFStringEncoding encoding);
-    // const UniChar *CFStringGetCharactersPtr(CFStringRef theString);
-    // CFIndex CFStringGetBytes(CFStringRef theString, CFRange range, CFStringEncoding encoding, UInt8 lossByte, Boolean isExternalRepresentation, UInt8 *buffer, CFIndex maxBufLen, CFIndex *usedBufLen);
+    pub fn CFStringGetPascalStringPtr(theString: CFStringRef, encoding: CFStringEncoding) -> ConstStringPtr;
+    pub fn CFStringGetCStringPtr(theString: CFStringRef, encoding: CFStringEncoding) -> *const c_char;
+    pub fn CFStringGetCharactersPtr(theString: CFStringRef) -> *const UniChar;
+    pub fn CFStringGetBytes(theString: CFStringRef, range: CFRange, encoding: CFStringEncoding, lossByte: UInt8, isExternalRepresentation: Boolean, buffer: *mut UInt8, maxBufLen: CFIndex, usedBufLen: *mut CFIndex) -> CFIndex;
     // CFStringRef CFStringCreateFromExternalRepresentation(CFAllocatorRef alloc, CFDataRef data, CFStringEncoding encoding);	/* May return NULL on conversion error */
     // CFDataRef CFStringCreateExternalRepresentation(CFAllocatorRef alloc, CFStringRef theString, CFStringEncoding encoding, UInt8 lossByte);	/* May return NULL on conversion error */	
     // CFStringEncoding CFStringGetSmallestEncoding(CFStringRef theString);	/* Result in O(n) time max */
     // CFStringEncoding CFStringGetFastestEncoding(CFStringRef theString);	/* Result in O(1) time max */
-    // CFStringEncoding CFStringGetSystemEncoding(void);		/* The default encoding for the system; untagged 8-bit characters are usually in this encoding */
+    pub fn CFStringGetSystemEncoding() -> CFStringEncoding;
     // CFIndex CFStringGetMaximumSizeForEncoding(CFIndex length, CFStringEncoding encoding);	/* Max bytes a string of specified length (in UniChars) will take up if encoded */
-    // Boolean CFStringGetFileSystemRepresentation(CFStringRef string, char *buffer, CFIndex maxBufLen);
-    // CFIndex CFStringGetMaximumSizeOfFileSystemRepresentation(CFStringRef string);
-    // CFStringRef CFStringCreateWithFileSystemRepresentation(CFAllocatorRef alloc, const char *buffer);
+    pub fn CFStringGetFileSystemRepresentation(string: CFStringRef, buffer: *mut c_char, maxBufLen: CFIndex) -> Boolean;
+    pub fn CFStringGetMaximumSizeOfFileSystemRepresentation(string: CFStringRef) -> CFIndex;
+    pub fn CFStringCreateWithFileSystemRepresentation(alloc: CFAllocatorRef, buffer: *const c_char) -> CFStringRef;
     // 
-    // CFComparisonResult CFStringCompareWithOptionsAndLocale(CFStringRef theString1, CFStringRef theString2, CFRange rangeToCompare, CFStringCompareFlags compareOptions, CFLocaleRef locale) API_AVAILABLE(macos(10.5), ios(2.0), watchos(2.0), tvos(9.0));
-    // CFComparisonResult CFStringCompareWithOptions(CFStringRef theString1, CFStringRef theString2, CFRange rangeToCompare, CFStringCompareFlags compareOptions);
-    // CFComparisonResult CFStringCompare(CFStringRef theString1, CFStringRef theString2, CFStringCompareFlags compareOptions);
-    // Boolean CFStringFindWithOptionsAndLocale(CFStringRef theString, CFStringRef stringToFind, CFRange rangeToSearch, CFStringCompareFlags searchOptions, CFLocaleRef locale, CFRange *result) API_AVAILABLE(macos(10.5), ios(2.0), watchos(2.0), tvos(9.0));
-    // Boolean CFStringFindWithOptions(CFStringRef theString, CFStringRef stringToFind, CFRange rangeToSearch, CFStringCompareFlags searchOptions, CFRange *result);
-    // CFArrayRef CFStringCreateArrayWithFindResults(CFAllocatorRef alloc, CFStringRef theString, CFStringRef stringToFind, CFRange rangeToSearch, CFStringCompareFlags compareOptions);
-    // CFRange CFStringFind(CFStringRef theString, CFStringRef stringToFind, CFStringCompareFlags compareOptions);
-    // Boolean CFStringHasPrefix(CFStringRef theString, CFStringRef prefix);
-    // Boolean CFStringHasSuffix(CFStringRef theString, CFStringRef suffix);
-    // CFRange CFStringGetRangeOfComposedCharactersAtIndex(CFStringRef theString, CFIndex theIndex);
-    // Boolean CFStringFindCharacterFromSet(CFStringRef theString, CFCharacterSetRef theSet, CFRange rangeToSearch, CFStringCompareFlags searchOptions, CFRange *result);
-    // void CFStringGetLineBounds(CFStringRef theString, CFRange range, CFIndex *lineBeginIndex, CFIndex *lineEndIndex, CFIndex *contentsEndIndex); 
-    // void CFStringGetParagraphBounds(CFStringRef string, CFRange range, CFIndex *parBeginIndex, CFIndex *parEndIndex, CFIndex *contentsEndIndex) API_AVAILABLE(macos(10.5), ios(2.0), watchos(2.0), tvos(9.0));
-    // CFIndex CFStringGetHyphenationLocationBeforeIndex(CFStringRef string, CFIndex location, CFRange limitRange, CFOptionFlags options, CFLocaleRef locale, UTF32Char *character) API_AVAILABLE(macos(10.7), ios(4.2), watchos(2.0), tvos(9.0));
-    // Boolean CFStringIsHyphenationAvailableForLocale(CFLocaleRef locale) API_AVAILABLE(macos(10.7), ios(4.3), watchos(2.0), tvos(9.0));
+    pub fn CFStringCompareWithOptionsAndLocale(theString1: CFStringRef, theString2: CFStringRef, rangeToCompare: CFRange, compareOptions: CFStringCompareFlags, locale: CFLocaleRef) -> CFComparisonResult;
+    pub fn CFStringCompareWithOptions(theString1: CFStringRef, theString2: CFStringRef, rangeToCompare: CFRange, compareOptions: CFStringCompareFlags) -> CFComparisonResult;
+    pub fn CFStringCompare(theString1: CFStringRef, theString2: CFStringRef, compareOptions: CFStringCompareFlags) -> CFComparisonResult;
+    pub fn CFStringFindWithOptionsAndLocale(theString: CFStringRef, stringToFind: CFStringRef, rangeToSearch: CFRange, searchOptions: CFStringCompareFlags, locale: CFLocaleRef, result: *mut CFRange) -> Boolean;
+    pub fn CFStringFindWithOptions(theString: CFStringRef, stringToFind: CFStringRef, rangeToSearch: CFRange, searchOptions: CFStringCompareFlags, result: *mut CFRange) -> Boolean;
+    pub fn CFStringCreateArrayWithFindResults(alloc: CFAllocatorRef, theString: CFStringRef, stringToFind: CFStringRef, rangeToSearch: CFRange, compareOptions: CFStringCompareFlags) -> CFArrayRef;
+    pub fn CFStringFind(theString: CFStringRef, stringToFind: CFStringRef, compareOptions: CFStringCompareFlags) -> CFRange;
+    pub fn CFStringHasPrefix(theString: CFStringRef, prefix: CFStringRef) -> Boolean;
+    pub fn CFStringHasSuffix(theString: CFStringRef, suffix: CFStringRef) -> Boolean;
+    pub fn CFStringGetRangeOfComposedCharactersAtIndex(theString: CFStringRef, theIndex: CFIndex) -> CFRange;
+    pub fn CFStringFindCharacterFromSet(theString: CFStringRef, theSet: CFCharacterSetRef, rangeToSearch: CFRange, searchOptions: CFStringCompareFlags, result: *mut CFRange) -> Boolean;
+    pub fn CFStringGetLineBounds(theString: CFStringRef, range: CFRange, lineBeginIndex: *mut CFIndex, lineEndIndex: *mut CFIndex, contentsEndIndex: *mut CFIndex);
+    pub fn CFStringGetParagraphBounds(string: CFStringRef, range: CFRange, parBeginIndex: *mut CFIndex, parEndIndex: *mut CFIndex, contentsEndIndex: *mut CFIndex);
+    pub fn CFStringGetHyphenationLocationBeforeIndex(string: CFStringRef, location: CFIndex, limitRange: CFRange, options: CFOptionFlags, locale: CFLocaleRef, character: *mut UTF32Char) -> CFIndex;
+    pub fn CFStringIsHyphenationAvailableForLocale(locale: CFLocaleRef) -> Boolean;
     // 
-    // CFStringRef CFStringCreateByCombiningStrings(CFAllocatorRef alloc, CFArrayRef theArray, CFStringRef separatorString);	/* Empty array returns empty string; one element array returns the element */
-    // CFArrayRef CFStringCreateArrayBySeparatingStrings(CFAllocatorRef alloc, CFStringRef theString, CFStringRef separatorString);	/* No separators in the string returns array with that string; string == sep returns two empty strings */
+    pub fn CFStringCreateByCombiningStrings(alloc: CFAllocatorRef, theArray: CFArrayRef, separatorString: CFStringRef) -> CFStringRef;
+    pub fn CFStringCreateArrayBySeparatingStrings(alloc: CFAllocatorRef, theString: CFStringRef, separatorString: CFStringRef) -> CFArrayRef;
     // 
-    // SInt32 CFStringGetIntValue(CFStringRef str);
-    // double CFStringGetDoubleValue(CFStringRef str);
+    pub fn CFStringGetIntValue(str: CFStringRef) -> SInt32;
+    pub fn CFStringGetDoubleValue(str: CFStringRef) -> Float64;
     // 
-    // void CFStringAppend(CFMutableStringRef theString, CFStringRef appendedString);
-    // void CFStringAppendCharacters(CFMutableStringRef theString, const UniChar *chars, CFIndex numChars);
-    // void CFStringAppendPascalString(CFMutableStringRef theString, ConstStr255Param pStr, CFStringEncoding encoding);
+    pub fn CFStringAppend(theString: CFMutableStringRef, appendedString: CFStringRef);
+    pub fn CFStringAppendCharacters(theString: CFMutableStringRef, chars: *const UniChar, numChars: CFIndex);
+    pub fn CFStringAppendPascalString(theString: CFMutableStringRef, pStr: ConstStr255Param, encoding: CFStringEncoding);
     // void CFStringAppendCString(CFMutableStringRef theString, const char *cStr, CFStringEncoding encoding);
     // void CFStringAppendFormat(CFMutableStringRef theString, CFDictionaryRef formatOptions, CFStringRef format, ...) CF_FORMAT_FUNCTION(3,4);
     // void CFStringAppendFormatAndArguments(CFMutableStringRef theString, CFDictionaryRef formatOptions, CFStringRef format, va_list arguments) CF_FORMAT_FUNCTION(3,0);
-    // void CFStringInsert(CFMutableStringRef str, CFIndex idx, CFStringRef insertedStr);
-    // void CFStringDelete(CFMutableStringRef theString, CFRange range);
-    // void CFStringReplace(CFMutableStringRef theString, CFRange range, CFStringRef replacement);
-    // void CFStringReplaceAll(CFMutableStringRef theString, CFStringRef replacement);	/* Replaces whole string */
-    // CFIndex CFStringFindAndReplace(CFMutableStringRef theString, CFStringRef stringToFind, CFStringRef replacementString, CFRange rangeToSearch, CFStringCompareFlags compareOptions);
+    pub fn CFStringInsert(str: CFMutableStringRef, idx: CFIndex, insertedStr: CFStringRef);
+    pub fn CFStringDelete(theString: CFMutableStringRef, range: CFRange);
+    pub fn CFStringReplace(theString: CFMutableStringRef, range: CFRange, replacement: CFStringRef);
+    pub fn CFStringReplaceAll(theString: CFMutableStringRef, replacement: CFStringRef);
+    pub fn CFStringFindAndReplace(theString: CFMutableStringRef, stringToFind: CFStringRef, replacementString: CFStringRef, rangeToSearch: CFRange, compareOptions: CFStringCompareFlags) -> CFIndex;
     // void CFStringSetExternalCharactersNoCopy(CFMutableStringRef theString, UniChar *chars, CFIndex length, CFIndex capacity);	/* Works only on specially created mutable strings! */
-    // void CFStringPad(CFMutableStringRef theString, CFStringRef padString, CFIndex length, CFIndex indexIntoPad);
-    // void CFStringTrim(CFMutableStringRef theString, CFStringRef trimString);
-    // void CFStringTrimWhitespace(CFMutableStringRef theString);
-    // void CFStringLowercase(CFMutableStringRef theString, CFLocaleRef locale);
-    // void CFStringUppercase(CFMutableStringRef theString, CFLocaleRef locale);
-    // void CFStringCapitalize(CFMutableStringRef theString, CFLocaleRef locale);
+    pub fn CFStringPad(theString: CFMutableStringRef, padString: CFStringRef, length: CFIndex, indexIntoPad: CFIndex);
+    pub fn CFStringTrim(theString: CFMutableStringRef, trimString: CFStringRef);
+    pub fn CFStringTrimWhitespace(theString: CFMutableStringRef);
+    pub fn CFStringLowercase(theString: CFMutableStringRef, locale: CFLocaleRef);
+    pub fn CFStringUppercase(theString: CFMutableStringRef, locale: CFLocaleRef);
+    pub fn CFStringCapitalize(theString: CFMutableStringRef, locale: CFLocaleRef);
     // 
-    // void CFStringNormalize(CFMutableStringRef theString, CFStringNormalizationForm theForm);
-    // void CFStringFold(CFMutableStringRef theString, CFStringCompareFlags theFlags, CFLocaleRef theLocale) API_AVAILABLE(macos(10.5), ios(2.0), watchos(2.0), tvos(9.0));
-    // Boolean CFStringTransform(CFMutableStringRef string, CFRange *range, CFStringRef transform, Boolean reverse);
+    pub fn CFStringNormalize(theString: CFMutableStringRef, theForm: CFStringNormalizationForm);
+    pub fn CFStringFold(theString: CFMutableStringRef, theFlags: CFStringCompareFlags, theLocale: CFLocaleRef);
+    pub fn CFStringTransform(string: CFMutableStringRef, range: *mut CFRange, transform: CFStringRef, reverse: Boolean) -> Boolean;
     // 
-    // const CFStringRef kCFStringTransformStripCombiningMarks;
-    // const CFStringRef kCFStringTransformToLatin;
-    // const CFStringRef kCFStringTransformFullwidthHalfwidth;
-    // const CFStringRef kCFStringTransformLatinKatakana;
-    // const CFStringRef kCFStringTransformLatinHiragana;
-    // const CFStringRef kCFStringTransformHiraganaKatakana;
-    // const CFStringRef kCFStringTransformMandarinLatin;
-    // const CFStringRef kCFStringTransformLatinHangul;
-    // const CFStringRef kCFStringTransformLatinArabic;
-    // const CFStringRef kCFStringTransformLatinHebrew;
-    // const CFStringRef kCFStringTransformLatinThai;
-    // const CFStringRef kCFStringTransformLatinCyrillic;
-    // const CFStringRef kCFStringTransformLatinGreek;
-    // const CFStringRef kCFStringTransformToXMLHex;
-    // const CFStringRef kCFStringTransformToUnicodeName;
-    // const CFStringRef kCFStringTransformStripDiacritics;
+    pub static kCFStringTransformStripCombiningMarks: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformToLatin: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformFullwidthHalfwidth: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinKatakana: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinHiragana: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformHiraganaKatakana: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformMandarinLatin: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinHangul: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinArabic: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinHebrew: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinThai: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinCyrillic: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformLatinGreek: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformToXMLHex: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformToUnicodeName: CFRef<'static, CFStringRef>;
+    pub static kCFStringTransformStripDiacritics: CFRef<'static, CFStringRef>;
     // 
-    // Boolean CFStringIsEncodingAvailable(CFStringEncoding encoding);
-    // const CFStringEncoding *CFStringGetListOfAvailableEncodings(void);
-    // CFStringRef CFStringGetNameOfEncoding(CFStringEncoding encoding);
-    // unsigned long CFStringConvertEncodingToNSStringEncoding(CFStringEncoding encoding);
-    // CFStringEncoding CFStringConvertNSStringEncodingToEncoding(unsigned long encoding);
-    // UInt32 CFStringConvertEncodingToWindowsCodepage(CFStringEncoding encoding);
-    // CFStringEncoding CFStringConvertWindowsCodepageToEncoding(UInt32 codepage);
-    // CFStringEncoding CFStringConvertIANACharSetNameToEncoding(CFStringRef theString);
-    // CFStringRef  CFStringConvertEncodingToIANACharSetName(CFStringEncoding encoding);
-    // CFStringEncoding CFStringGetMostCompatibleMacStringEncoding(CFStringEncoding encoding);
+    pub fn CFStringIsEncodingAvailable(encoding: CFStringEncoding) -> Boolean;
+    pub fn CFStringGetListOfAvailableEncodings() -> *const CFStringEncoding;
+    pub fn CFStringGetNameOfEncoding(encoding: CFStringEncoding) -> CFStringRef;
+    pub fn CFStringConvertEncodingToNSStringEncoding(encoding: CFStringEncoding) -> c_ulong;
+    pub fn CFStringConvertNSStringEncodingToEncoding(encoding: c_ulong) -> CFStringEncoding;
+    pub fn CFStringConvertEncodingToWindowsCodepage(encoding: CFStringEncoding) -> UInt32;
+    pub fn CFStringConvertWindowsCodepageToEncoding(codepage: UInt32) -> CFStringEncoding;
+    pub fn CFStringConvertIANACharSetNameToEncoding(theString: CFStringRef) -> CFStringEncoding;
+    pub fn CFStringConvertEncodingToIANACharSetName(encoding: CFStringEncoding) -> CFStringRef;
+    pub fn CFStringGetMostCompatibleMacStringEncoding(encoding: CFStringEncoding) -> CFStringEncoding;
 
     fn CFShowStr(string: CFStringRef);
+
+    pub fn __CFStringMakeConstantString(cStr: *const c_char) -> CFStringRef;
+  }
+}
+
+#[cf_type(parent = CFTypeRef, type_id = CFStringGetTypeID, owned = CFString)]
+pub struct CFStringRef;
+
+#[cf_type(parent = CFStringRef, owned = CFMutableString, clone = copy_mutable_string)]
+pub struct CFMutableStringRef;
+
+// A retained clone would let one owner edit characters that the other has borrowed through `to_str`.
+fn copy_mutable_string(string: &CFMutableString) -> CFMutableString {
+  return CFStringCreateMutableCopy(kCFAllocatorDefault, 0, string).expect("could not allocate CFMutableString");
+}
+
+pub fn CFStringGetTypeID() -> CFTypeID {
+  return unsafe { ext::CFStringGetTypeID() };
+}
+
+pub fn CFStringCreateWithPascalString<A: Subtype<CFAllocatorRef>>(alloc: &A, pStr: &PascalStr, encoding: CFStringEncoding) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithPascalString(alloc.upcast(), pStr.as_ptr(), encoding)) };
+}
+
+pub fn CFStringCreateWithCString<A: Subtype<CFAllocatorRef>>(alloc: &A, cStr: &CStr, encoding: CFStringEncoding) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithCString(alloc.upcast(), cStr.as_ptr(), encoding)) };
+}
+
+pub fn CFStringCreateWithBytes<A: Subtype<CFAllocatorRef>>(alloc: &A, bytes: &[u8], encoding: CFStringEncoding, isExternalRepresentation: bool) -> Option<CFString> {
+  let isExternalRepresentation = if isExternalRepresentation { Boolean::TRUE } else { Boolean::FALSE };
+
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithBytes(alloc.upcast(), bytes.as_ptr(), bytes.len() as CFIndex, encoding, isExternalRepresentation)) };
+}
+
+pub fn CFStringCreateWithCharacters<A: Subtype<CFAllocatorRef>>(alloc: &A, chars: &[UniChar]) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithCharacters(alloc.upcast(), chars.as_ptr(), chars.len() as CFIndex)) };
+}
+
+/// The string reads `bytes` in place, and they are dropped once Core Foundation no longer needs them.
+pub fn CFStringCreateWithBytesNoCopy<A: Subtype<CFAllocatorRef>>(alloc: &A, bytes: Box<[u8]>, encoding: CFStringEncoding, isExternalRepresentation: bool) -> Option<CFString> {
+  let isExternalRepresentation = if isExternalRepresentation { Boolean::TRUE } else { Boolean::FALSE };
+  let (ptr, length) = (bytes.as_ptr(), bytes.len());
+  let contentsDeallocator = buffer_deallocator(bytes.into_vec());
+
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithBytesNoCopy(alloc.upcast(), ptr, length as CFIndex, encoding, isExternalRepresentation, contentsDeallocator.upcast())) };
+}
+
+/// The string reads `chars` in place, and they are dropped once Core Foundation no longer needs them.
+pub fn CFStringCreateWithCharactersNoCopy<A: Subtype<CFAllocatorRef>>(alloc: &A, chars: Vec<UniChar>) -> Option<CFString> {
+  let (ptr, length) = (chars.as_ptr(), chars.len());
+  let contentsDeallocator = buffer_deallocator(chars);
+
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithCharactersNoCopy(alloc.upcast(), ptr, length as CFIndex, contentsDeallocator.upcast())) };
+}
+
+pub fn CFStringCreateWithSubstring<A: Subtype<CFAllocatorRef>, S: Subtype<CFStringRef>>(alloc: &A, str: &S, range: CFRange) -> Option<CFString> {
+  if !is_valid_range(str, range) {
+    return None;
+  }
+
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithSubstring(alloc.upcast(), str.upcast(), range)) };
+}
+
+pub fn CFStringCreateCopy<A: Subtype<CFAllocatorRef>, S: Subtype<CFStringRef>>(alloc: &A, theString: &S) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateCopy(alloc.upcast(), theString.upcast())) };
+}
+
+/// A `maxLength` of 0 means the string can grow without limit.
+pub fn CFStringCreateMutable<A: Subtype<CFAllocatorRef>>(alloc: &A, maxLength: CFIndex) -> Option<CFMutableString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateMutable(alloc.upcast(), maxLength)) };
+}
+
+pub fn CFStringCreateMutableCopy<A: Subtype<CFAllocatorRef>, S: Subtype<CFStringRef>>(alloc: &A, maxLength: CFIndex, theString: &S) -> Option<CFMutableString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateMutableCopy(alloc.upcast(), maxLength, theString.upcast())) };
+}
+
+/// The string edits `chars` in place up to its capacity, moving to memory of its own when it has to grow, and `chars`
+/// is dropped once Core Foundation no longer needs it.
+pub fn CFStringCreateMutableWithExternalCharactersNoCopy<A: Subtype<CFAllocatorRef>>(alloc: &A, mut chars: Vec<UniChar>) -> Option<CFMutableString> {
+  let (ptr, length, capacity) = (chars.as_mut_ptr(), chars.len(), chars.capacity());
+  let externalCharactersAllocator = buffer_deallocator(chars);
+
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateMutableWithExternalCharactersNoCopy(alloc.upcast(), ptr, length as CFIndex, capacity as CFIndex, externalCharactersAllocator.upcast())) };
+}
+
+pub fn CFStringGetLength<S: Subtype<CFStringRef>>(theString: &S) -> CFIndex {
+  return unsafe { ext::CFStringGetLength(theString.upcast()) };
+}
+
+pub fn CFStringGetCharacters<S: Subtype<CFStringRef>>(theString: &S, range: CFRange, buffer: &mut [UniChar]) {
+  assert!(is_valid_range(theString, range), "range out of bounds");
+  assert!(buffer.len() as CFIndex >= range.length, "buffer too small");
+
+  unsafe { ext::CFStringGetCharacters(theString.upcast(), range, buffer.as_mut_ptr()) };
+}
+
+/// Writes a length byte and the encoded text into `buffer`, failing if the text does not fit or cannot be encoded.
+pub fn CFStringGetPascalString<S: Subtype<CFStringRef>>(theString: &S, buffer: &mut [u8], encoding: CFStringEncoding) -> Boolean {
+  return unsafe { ext::CFStringGetPascalString(theString.upcast(), buffer.as_mut_ptr(), buffer.len() as CFIndex, encoding) };
+}
+
+/// The `*Ptr` functions borrow the string's own storage. Any other handle to a mutable string can change it, so the
+/// caller must make sure that does not happen while the borrow is alive.
+pub unsafe fn CFStringGetPascalStringPtr<'a, S: Subtype<CFStringRef>>(theString: &'a S, encoding: CFStringEncoding) -> Option<&'a PascalStr> {
+  let ptr = ext::CFStringGetPascalStringPtr(theString.upcast(), encoding);
+
+  if ptr.is_null() {
+    return None;
+  }
+
+  return Some(PascalStr::from_ptr(ptr));
+}
+
+pub unsafe fn CFStringGetCStringPtr<'a, S: Subtype<CFStringRef>>(theString: &'a S, encoding: CFStringEncoding) -> Option<&'a CStr> {
+  let ptr = ext::CFStringGetCStringPtr(theString.upcast(), encoding);
+
+  if ptr.is_null() {
+    return None;
   }
+
+  return Some(CStr::from_ptr(ptr));
 }
 
-#[repr(transparent)] pub struct CFStringRef(usize);
-#[repr(transparent)] pub struct CFMutableStringRef(usize);
+pub unsafe fn CFStringGetCharactersPtr<'a, S: Subtype<CFStringRef>>(theString: &'a S) -> Option<&'a [UniChar]> {
+  let ptr = ext::CFStringGetCharactersPtr(theString.upcast());
 
-unsafe impl Subtype<CFTypeRef> for CFStringRef {
-  unsafe fn upcast(&self) -> CFTypeRef {
-    return CFTypeRef(self.0);
+  if ptr.is_null() {
+    return None;
   }
+
+  return Some(slice::from_raw_parts(ptr, CFStringGetLength(theString) as usize));
+}
+
+// Copying an immutable string with its own allocator returns the same object, while a mutable one is always copied.
+fn is_immutable<S: Subtype<CFStringRef>>(string: &S) -> bool {
+  let string: CFStringRef = unsafe { string.upcast() };
+  let copy = CFStringCreateCopy(&CFGetAllocator(&string), &string).expect("could not allocate CFString");
+  let copy: CFStringRef = unsafe { copy.upcast() };
+
+  return copy.0 == string.0;
+}
+
+/// Returns the number of characters converted and the number of bytes they take up. If `buffer` is
+/// `None` nothing is written, which can be used to size a buffer before converting.
+pub fn CFStringGetBytes<S: Subtype<CFStringRef>>(theString: &S, range: CFRange, encoding: CFStringEncoding, lossByte: UInt8, isExternalRepresentation: bool, buffer: Option<&mut [u8]>) -> (CFIndex, CFIndex) {
+  assert!(is_valid_range(theString, range), "range out of bounds");
+
+  let isExternalRepresentation = if isExternalRepresentation { Boolean::TRUE } else { Boolean::FALSE };
+  let (ptr, len) = match buffer {
+    Some(buffer) => (buffer.as_mut_ptr(), buffer.len() as CFIndex),
+    None => (ptr::null_mut(), 0)
+  };
+
+  let mut usedBufLen = 0;
+  let converted = unsafe { ext::CFStringGetBytes(theString.upcast(), range, encoding, lossByte, isExternalRepresentation, ptr, len, &mut usedBufLen) };
+
+  return (converted, usedBufLen);
+}
+
+pub fn CFStringGetSystemEncoding() -> CFStringEncoding {
+  return unsafe { ext::CFStringGetSystemEncoding() };
+}
+
+/// Writes a NUL-terminated path in the encoding the file system expects, decomposing characters if it requires.
+pub fn CFStringGetFileSystemRepresentation<S: Subtype<CFStringRef>>(string: &S, buffer: &mut [u8]) -> Boolean {
+  return unsafe { ext::CFStringGetFileSystemRepresentation(string.upcast(), buffer.as_mut_ptr() as *mut c_char, buffer.len() as CFIndex) };
+}
+
+pub fn CFStringGetMaximumSizeOfFileSystemRepresentation<S: Subtype<CFStringRef>>(string: &S) -> CFIndex {
+  return unsafe { ext::CFStringGetMaximumSizeOfFileSystemRepresentation(string.upcast()) };
+}
+
+pub fn CFStringCreateWithFileSystemRepresentation<A: Subtype<CFAllocatorRef>>(alloc: &A, buffer: &CStr) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateWithFileSystemRepresentation(alloc.upcast(), buffer.as_ptr())) };
+}
+
+pub fn CFStringCompareWithOptionsAndLocale<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>, L: Subtype<CFLocaleRef>>(theString1: &S1, theString2: &S2, rangeToCompare: CFRange, compareOptions: CFStringCompareFlags, locale: Option<&L>) -> CFComparisonResult {
+  assert!(is_valid_range(theString1, rangeToCompare), "range out of bounds");
+
+  return unsafe {
+    let locale = locale.map_or(CFLocaleRef(0), |locale| locale.upcast());
+    ext::CFStringCompareWithOptionsAndLocale(theString1.upcast(), theString2.upcast(), rangeToCompare, compareOptions, locale)
+  };
+}
+
+pub fn CFStringCompareWithOptions<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString1: &S1, theString2: &S2, rangeToCompare: CFRange, compareOptions: CFStringCompareFlags) -> CFComparisonResult {
+  assert!(is_valid_range(theString1, rangeToCompare), "range out of bounds");
+
+  return unsafe { ext::CFStringCompareWithOptions(theString1.upcast(), theString2.upcast(), rangeToCompare, compareOptions) };
+}
+
+pub fn CFStringCompare<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString1: &S1, theString2: &S2, compareOptions: CFStringCompareFlags) -> CFComparisonResult {
+  return unsafe { ext::CFStringCompare(theString1.upcast(), theString2.upcast(), compareOptions) };
+}
+
+pub fn CFStringFindWithOptionsAndLocale<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>, L: Subtype<CFLocaleRef>>(theString: &S1, stringToFind: &S2, rangeToSearch: CFRange, searchOptions: CFStringCompareFlags, locale: Option<&L>) -> Option<CFRange> {
+  assert!(is_valid_range(theString, rangeToSearch), "range out of bounds");
+
+  let mut result = CFRangeMake(kCFNotFound, 0);
+  let found = unsafe {
+    let locale = locale.map_or(CFLocaleRef(0), |locale| locale.upcast());
+    ext::CFStringFindWithOptionsAndLocale(theString.upcast(), stringToFind.upcast(), rangeToSearch, searchOptions, locale, &mut result)
+  };
+
+  return if found == Boolean::TRUE { Some(result) } else { None };
+}
+
+pub fn CFStringFindWithOptions<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString: &S1, stringToFind: &S2, rangeToSearch: CFRange, searchOptions: CFStringCompareFlags) -> Option<CFRange> {
+  assert!(is_valid_range(theString, rangeToSearch), "range out of bounds");
+
+  let mut result = CFRangeMake(kCFNotFound, 0);
+  let found = unsafe { ext::CFStringFindWithOptions(theString.upcast(), stringToFind.upcast(), rangeToSearch, searchOptions, &mut result) };
+
+  return if found == Boolean::TRUE { Some(result) } else { None };
+}
+
+/// Returns an array of `CFRange` pointers, or `None` if there are no matches.
+pub fn CFStringCreateArrayWithFindResults<A: Subtype<CFAllocatorRef>, S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(alloc: &A, theString: &S1, stringToFind: &S2, rangeToSearch: CFRange, compareOptions: CFStringCompareFlags) -> Option<CFType<CFArrayRef>> {
+  assert!(is_valid_range(theString, rangeToSearch), "range out of bounds");
+
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateArrayWithFindResults(alloc.upcast(), theString.upcast(), stringToFind.upcast(), rangeToSearch, compareOptions)) };
+}
+
+pub fn CFStringFind<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString: &S1, stringToFind: &S2, compareOptions: CFStringCompareFlags) -> Option<CFRange> {
+  let result = unsafe { ext::CFStringFind(theString.upcast(), stringToFind.upcast(), compareOptions) };
+
+  return if result.location == kCFNotFound { None } else { Some(result) };
+}
+
+pub fn CFStringHasPrefix<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString: &S1, prefix: &S2) -> Boolean {
+  return unsafe { ext::CFStringHasPrefix(theString.upcast(), prefix.upcast()) };
+}
+
+pub fn CFStringHasSuffix<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString: &S1, suffix: &S2) -> Boolean {
+  return unsafe { ext::CFStringHasSuffix(theString.upcast(), suffix.upcast()) };
+}
+
+pub fn CFStringFindCharacterFromSet<S: Subtype<CFStringRef>, C: Subtype<CFCharacterSetRef>>(theString: &S, theSet: &C, rangeToSearch: CFRange, searchOptions: CFStringCompareFlags) -> Option<CFRange> {
+  assert!(is_valid_range(theString, rangeToSearch), "range out of bounds");
+
+  let mut result = CFRangeMake(kCFNotFound, 0);
+  let found = unsafe { ext::CFStringFindCharacterFromSet(theString.upcast(), theSet.upcast(), rangeToSearch, searchOptions, &mut result) };
+
+  return if found == Boolean::TRUE { Some(result) } else { None };
+}
+
+pub fn CFStringGetRangeOfComposedCharactersAtIndex<S: Subtype<CFStringRef>>(theString: &S, theIndex: CFIndex) -> CFRange {
+  assert!(theIndex >= 0 && theIndex < CFStringGetLength(theString), "index out of bounds");
+
+  return unsafe { ext::CFStringGetRangeOfComposedCharactersAtIndex(theString.upcast(), theIndex) };
+}
+
+/// Returns the start and end of the lines containing `range`, and where their contents end before the line terminator.
+pub fn CFStringGetLineBounds<S: Subtype<CFStringRef>>(theString: &S, range: CFRange) -> (CFIndex, CFIndex, CFIndex) {
+  assert!(is_valid_range(theString, range), "range out of bounds");
+
+  let (mut lineBeginIndex, mut lineEndIndex, mut contentsEndIndex) = (0, 0, 0);
+  unsafe { ext::CFStringGetLineBounds(theString.upcast(), range, &mut lineBeginIndex, &mut lineEndIndex, &mut contentsEndIndex) };
+
+  return (lineBeginIndex, lineEndIndex, contentsEndIndex);
+}
+
+/// Like `CFStringGetLineBounds`, but only paragraph separators end a paragraph.
+pub fn CFStringGetParagraphBounds<S: Subtype<CFStringRef>>(string: &S, range: CFRange) -> (CFIndex, CFIndex, CFIndex) {
+  assert!(is_valid_range(string, range), "range out of bounds");
+
+  let (mut parBeginIndex, mut parEndIndex, mut contentsEndIndex) = (0, 0, 0);
+  unsafe { ext::CFStringGetParagraphBounds(string.upcast(), range, &mut parBeginIndex, &mut parEndIndex, &mut contentsEndIndex) };
+
+  return (parBeginIndex, parEndIndex, contentsEndIndex);
 }
 
-unsafe impl Subtype<CFStringRef> for CFStringRef {
-  unsafe fn upcast(&self) -> CFStringRef {
-    return CFStringRef(self.0);
+/// Returns the index of the last hyphenation opportunity before `location` within `limitRange`, and the
+/// character at it. The options argument is reserved, so it is always passed as 0. Returns `None` rather than letting
+/// Core Foundation raise if `limitRange` is out of bounds or does not contain `location`.
+pub fn CFStringGetHyphenationLocationBeforeIndex<S: Subtype<CFStringRef>, L: Subtype<CFLocaleRef>>(string: &S, location: CFIndex, limitRange: CFRange, locale: &L) -> Option<(CFIndex, UTF32Char)> {
+  if !is_valid_range(string, limitRange) || location < limitRange.location || location > limitRange.location + limitRange.length {
+    return None;
   }
+
+  let mut character = 0;
+  let index = unsafe { ext::CFStringGetHyphenationLocationBeforeIndex(string.upcast(), location, limitRange, CFOptionFlags(0), locale.upcast(), &mut character) };
+
+  return if index == kCFNotFound { None } else { Some((index, character)) };
 }
 
-unsafe impl Subtype<CFTypeRef> for CFMutableStringRef {
-  unsafe fn upcast(&self) -> CFTypeRef {
-    return CFTypeRef(self.0);
+pub fn CFStringIsHyphenationAvailableForLocale<L: Subtype<CFLocaleRef>>(locale: &L) -> Boolean {
+  return unsafe { ext::CFStringIsHyphenationAvailableForLocale(locale.upcast()) };
+}
+
+pub fn CFStringCreateByCombiningStrings<A: Subtype<CFAllocatorRef>, T: Subtype<CFStringRef>, S: Subtype<CFStringRef>>(alloc: &A, theArray: &CFArray<T>, separatorString: &S) -> Option<CFString> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateByCombiningStrings(alloc.upcast(), theArray.upcast(), separatorString.upcast())) };
+}
+
+pub fn CFStringCreateArrayBySeparatingStrings<A: Subtype<CFAllocatorRef>, S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(alloc: &A, theString: &S1, separatorString: &S2) -> Option<CFArray<CFStringRef>> {
+  return unsafe { CFType::try_from_create_rule(ext::CFStringCreateArrayBySeparatingStrings(alloc.upcast(), theString.upcast(), separatorString.upcast())).map(|array| CFArray::from_untyped_unchecked(array)) };
+}
+
+/// Returns 0 if the string does not start with a number.
+pub fn CFStringGetIntValue<S: Subtype<CFStringRef>>(str: &S) -> SInt32 {
+  return unsafe { ext::CFStringGetIntValue(str.upcast()) };
+}
+
+/// Returns 0 if the string does not start with a number.
+pub fn CFStringGetDoubleValue<S: Subtype<CFStringRef>>(str: &S) -> Float64 {
+  return unsafe { ext::CFStringGetDoubleValue(str.upcast()) };
+}
+
+pub fn CFStringAppend<M: Subtype<CFMutableStringRef>, S: Subtype<CFStringRef>>(theString: &mut M, appendedString: &S) {
+  unsafe { ext::CFStringAppend(theString.upcast(), appendedString.upcast()) };
+}
+
+pub fn CFStringAppendCharacters<M: Subtype<CFMutableStringRef>>(theString: &mut M, chars: &[UniChar]) {
+  unsafe { ext::CFStringAppendCharacters(theString.upcast(), chars.as_ptr(), chars.len() as CFIndex) };
+}
+
+pub fn CFStringAppendPascalString<M: Subtype<CFMutableStringRef>>(theString: &mut M, pStr: &PascalStr, encoding: CFStringEncoding) {
+  unsafe { ext::CFStringAppendPascalString(theString.upcast(), pStr.as_ptr(), encoding) };
+}
+
+pub fn CFStringInsert<M: Subtype<CFMutableStringRef> + Subtype<CFStringRef>, S: Subtype<CFStringRef>>(str: &mut M, idx: CFIndex, insertedStr: &S) {
+  assert!(idx >= 0 && idx <= CFStringGetLength(str), "index out of bounds");
+
+  unsafe { ext::CFStringInsert(str.upcast(), idx, insertedStr.upcast()) };
+}
+
+pub fn CFStringDelete<M: Subtype<CFMutableStringRef> + Subtype<CFStringRef>>(theString: &mut M, range: CFRange) {
+  assert!(is_valid_range(theString, range), "range out of bounds");
+
+  unsafe { ext::CFStringDelete(theString.upcast(), range) };
+}
+
+pub fn CFStringReplace<M: Subtype<CFMutableStringRef> + Subtype<CFStringRef>, S: Subtype<CFStringRef>>(theString: &mut M, range: CFRange, replacement: &S) {
+  assert!(is_valid_range(theString, range), "range out of bounds");
+
+  unsafe { ext::CFStringReplace(theString.upcast(), range, replacement.upcast()) };
+}
+
+pub fn CFStringReplaceAll<M: Subtype<CFMutableStringRef>, S: Subtype<CFStringRef>>(theString: &mut M, replacement: &S) {
+  unsafe { ext::CFStringReplaceAll(theString.upcast(), replacement.upcast()) };
+}
+
+/// Returns the number of occurrences that were replaced.
+pub fn CFStringFindAndReplace<M: Subtype<CFMutableStringRef> + Subtype<CFStringRef>, S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(theString: &mut M, stringToFind: &S1, replacementString: &S2, rangeToSearch: CFRange, compareOptions: CFStringCompareFlags) -> CFIndex {
+  assert!(is_valid_range(theString, rangeToSearch), "range out of bounds");
+
+  return unsafe { ext::CFStringFindAndReplace(theString.upcast(), stringToFind.upcast(), replacementString.upcast(), rangeToSearch, compareOptions) };
+}
+
+/// Pads with repetitions of `padString` starting at `indexIntoPad`, or truncates, until the string is `length` long.
+pub fn CFStringPad<M: Subtype<CFMutableStringRef> + Subtype<CFStringRef>, S: Subtype<CFStringRef>>(theString: &mut M, padString: &S, length: CFIndex, indexIntoPad: CFIndex) {
+  assert!(length >= 0, "length is negative");
+
+  if length > CFStringGetLength(theString) {
+    assert!(indexIntoPad >= 0 && indexIntoPad < CFStringGetLength(padString), "index into pad out of bounds");
   }
+
+  unsafe { ext::CFStringPad(theString.upcast(), padString.upcast(), length, indexIntoPad) };
 }
 
-unsafe impl Subtype<CFStringRef> for CFMutableStringRef {
-  unsafe fn upcast(&self) -> CFStringRef {
-    return CFStringRef(self.0);
+pub fn CFStringTrim<M: Subtype<CFMutableStringRef>, S: Subtype<CFStringRef>>(theString: &mut M, trimString: &S) {
+  unsafe { ext::CFStringTrim(theString.upcast(), trimString.upcast()) };
+}
+
+pub fn CFStringTrimWhitespace<M: Subtype<CFMutableStringRef>>(theString: &mut M) {
+  unsafe { ext::CFStringTrimWhitespace(theString.upcast()) };
+}
+
+pub fn CFStringLowercase<M: Subtype<CFMutableStringRef>, L: Subtype<CFLocaleRef>>(theString: &mut M, locale: Option<&L>) {
+  unsafe { ext::CFStringLowercase(theString.upcast(), locale.map_or(CFLocaleRef(0), |locale| locale.upcast())) };
+}
+
+pub fn CFStringUppercase<M: Subtype<CFMutableStringRef>, L: Subtype<CFLocaleRef>>(theString: &mut M, locale: Option<&L>) {
+  unsafe { ext::CFStringUppercase(theString.upcast(), locale.map_or(CFLocaleRef(0), |locale| locale.upcast())) };
+}
+
+pub fn CFStringCapitalize<M: Subtype<CFMutableStringRef>, L: Subtype<CFLocaleRef>>(theString: &mut M, locale: Option<&L>) {
+  unsafe { ext::CFStringCapitalize(theString.upcast(), locale.map_or(CFLocaleRef(0), |locale| locale.upcast())) };
+}
+
+pub fn CFStringNormalize<M: Subtype<CFMutableStringRef>>(theString: &mut M, theForm: CFStringNormalizationForm) {
+  unsafe { ext::CFStringNormalize(theString.upcast(), theForm) };
+}
+
+/// Only `kCFCompareCaseInsensitive`, `kCFCompareDiacriticInsensitive` and `kCFCompareWidthInsensitive` are meaningful in `theFlags`.
+pub fn CFStringFold<M: Subtype<CFMutableStringRef>, L: Subtype<CFLocaleRef>>(theString: &mut M, theFlags: CFStringCompareFlags, theLocale: Option<&L>) {
+  unsafe { ext::CFStringFold(theString.upcast(), theFlags, theLocale.map_or(CFLocaleRef(0), |locale| locale.upcast())) };
+}
+
+/// Transforms `range`, or the whole string if it is `None`, updating `range` to cover the result.
+pub fn CFStringTransform<M: Subtype<CFMutableStringRef> + Subtype<CFStringRef>, S: Subtype<CFStringRef>>(string: &mut M, range: Option<&mut CFRange>, transform: &S, reverse: bool) -> Boolean {
+  let reverse = if reverse { Boolean::TRUE } else { Boolean::FALSE };
+  let range = match range {
+    Some(range) => {
+      assert!(is_valid_range(string, *range), "range out of bounds");
+      range as *mut CFRange
+    },
+    None => ptr::null_mut()
+  };
+
+  return unsafe { ext::CFStringTransform(string.upcast(), range, transform.upcast(), reverse) };
+}
+
+pub static kCFStringTransformStripCombiningMarks: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformStripCombiningMarks };
+pub static kCFStringTransformToLatin: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformToLatin };
+pub static kCFStringTransformFullwidthHalfwidth: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformFullwidthHalfwidth };
+pub static kCFStringTransformLatinKatakana: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinKatakana };
+pub static kCFStringTransformLatinHiragana: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinHiragana };
+pub static kCFStringTransformHiraganaKatakana: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformHiraganaKatakana };
+pub static kCFStringTransformMandarinLatin: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformMandarinLatin };
+pub static kCFStringTransformLatinHangul: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinHangul };
+pub static kCFStringTransformLatinArabic: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinArabic };
+pub static kCFStringTransformLatinHebrew: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinHebrew };
+pub static kCFStringTransformLatinThai: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinThai };
+pub static kCFStringTransformLatinCyrillic: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinCyrillic };
+pub static kCFStringTransformLatinGreek: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformLatinGreek };
+pub static kCFStringTransformToXMLHex: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformToXMLHex };
+pub static kCFStringTransformToUnicodeName: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformToUnicodeName };
+pub static kCFStringTransformStripDiacritics: &'static CFRef<'static, CFStringRef> = unsafe { &ext::kCFStringTransformStripDiacritics };
+
+pub fn CFStringIsEncodingAvailable(encoding: CFStringEncoding) -> Boolean {
+  return unsafe { ext::CFStringIsEncodingAvailable(encoding) };
+}
+
+pub fn CFStringGetListOfAvailableEncodings() -> &'static [CFStringEncoding] {
+  unsafe {
+    let list = ext::CFStringGetListOfAvailableEncodings();
+    let mut length = 0;
+
+    while *list.offset(length) != CFStringEncoding::kCFStringEncodingInvalidId {
+      length += 1;
+    }
+
+    return slice::from_raw_parts(list, length as usize);
   }
 }
 
-unsafe impl Subtype<CFMutableStringRef> for CFMutableStringRef {
-  unsafe fn upcast(&self) -> CFMutableStringRef {
-    return CFMutableStringRef(self.0);
+pub fn CFStringGetNameOfEncoding(encoding: CFStringEncoding) -> Option<CFRef<'static, CFStringRef>> {
+  return unsafe { CFRef::try_from_get_rule(ext::CFStringGetNameOfEncoding(encoding)) };
+}
+
+pub fn CFStringConvertEncodingToNSStringEncoding(encoding: CFStringEncoding) -> c_ulong {
+  return unsafe { ext::CFStringConvertEncodingToNSStringEncoding(encoding) };
+}
+
+pub fn CFStringConvertNSStringEncodingToEncoding(encoding: c_ulong) -> CFStringEncoding {
+  return unsafe { ext::CFStringConvertNSStringEncodingToEncoding(encoding) };
+}
+
+pub fn CFStringConvertEncodingToWindowsCodepage(encoding: CFStringEncoding) -> UInt32 {
+  return unsafe { ext::CFStringConvertEncodingToWindowsCodepage(encoding) };
+}
+
+pub fn CFStringConvertWindowsCodepageToEncoding(codepage: UInt32) -> CFStringEncoding {
+  return unsafe { ext::CFStringConvertWindowsCodepageToEncoding(codepage) };
+}
+
+pub fn CFStringConvertIANACharSetNameToEncoding<S: Subtype<CFStringRef>>(theString: &S) -> CFStringEncoding {
+  return unsafe { ext::CFStringConvertIANACharSetNameToEncoding(theString.upcast()) };
+}
+
+pub fn CFStringConvertEncodingToIANACharSetName(encoding: CFStringEncoding) -> Option<CFRef<'static, CFStringRef>> {
+  return unsafe { CFRef::try_from_get_rule(ext::CFStringConvertEncodingToIANACharSetName(encoding)) };
+}
+
+pub fn CFStringGetMostCompatibleMacStringEncoding(encoding: CFStringEncoding) -> CFStringEncoding {
+  return unsafe { ext::CFStringGetMostCompatibleMacStringEncoding(encoding) };
+}
+
+fn is_valid_range<S: Subtype<CFStringRef>>(theString: &S, range: CFRange) -> bool {
+  return range.location >= 0 && range.length >= 0 && range.location + range.length <= CFStringGetLength(theString);
+}
+
+// Core Foundation returns 0 both for a zero and for text that does not start with a number, and saturates integers
+// that overflow. This finds the number it scans, if any: leading whitespace, at most one sign, then digits with an
+// optional decimal point.
+fn leading_number(string: &str, decimal: bool) -> Option<&str> {
+  let string = string.trim_start();
+  let sign = if string.starts_with('+') || string.starts_with('-') { 1 } else { 0 };
+  let mut end = sign;
+  let mut digits = 0;
+  let mut point = false;
+
+  for c in string[sign..].chars() {
+    if c.is_ascii_digit() {
+      digits += 1;
+    } else if c == '.' && decimal && !point {
+      point = true;
+    } else {
+      break;
+    }
+
+    end += 1;
+  }
+
+  return if digits > 0 { Some(&string[..end]) } else { None };
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CFStringEncodingError {
+  pub encoding: CFStringEncoding
+}
+
+impl fmt::Display for CFStringEncodingError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return write!(f, "bytes are not valid in {:?}", self.encoding);
+  }
+}
+
+impl Error for CFStringEncodingError {
+  fn description(&self) -> &str {
+    return "bytes are not valid in the string encoding";
+  }
+}
+
+impl CFString {
+  pub fn from_str(string: &str) -> CFString {
+    return CFString::from_bytes(string.as_bytes(), CFStringEncoding::kCFStringEncodingUTF8).expect("could not allocate CFString");
+  }
+
+  pub fn from_utf16(chars: &[UniChar]) -> CFString {
+    return CFStringCreateWithCharacters(kCFAllocatorDefault, chars).expect("could not allocate CFString");
+  }
+
+  /// Like `from_utf16`, but without copying `chars`.
+  pub fn from_utf16_vec(chars: Vec<UniChar>) -> CFString {
+    return CFStringCreateWithCharactersNoCopy(kCFAllocatorDefault, chars).expect("could not allocate CFString");
+  }
+
+  pub fn from_bytes(bytes: &[u8], encoding: CFStringEncoding) -> Result<CFString, CFStringEncodingError> {
+    return CFStringCreateWithBytes(kCFAllocatorDefault, bytes, encoding, false).ok_or(CFStringEncodingError { encoding: encoding });
+  }
+
+  pub fn from_pascal_str(string: &PascalStr, encoding: CFStringEncoding) -> Result<CFString, CFStringEncodingError> {
+    return CFStringCreateWithPascalString(kCFAllocatorDefault, string, encoding).ok_or(CFStringEncodingError { encoding: encoding });
+  }
+}
+
+impl CFMutableString {
+  pub fn new() -> CFMutableString {
+    return CFStringCreateMutable(kCFAllocatorDefault, 0).expect("could not allocate CFMutableString");
+  }
+
+  /// Edits `chars` in place until the string outgrows its capacity.
+  pub fn from_utf16_vec(chars: Vec<UniChar>) -> CFMutableString {
+    return CFStringCreateMutableWithExternalCharactersNoCopy(kCFAllocatorDefault, chars).expect("could not allocate CFMutableString");
+  }
+
+  pub fn from_str(string: &str) -> CFMutableString {
+    let mut mutable = CFMutableString::new();
+    mutable.append_str(string);
+
+    return mutable;
+  }
+}
+
+impl Default for CFMutableString {
+  fn default() -> CFMutableString {
+    return CFMutableString::new();
+  }
+}
+
+impl<'a> From<&'a str> for CFMutableString {
+  fn from(string: &'a str) -> CFMutableString {
+    return CFMutableString::from_str(string);
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CFStringPathError {
+  pub path: PathBuf
+}
+
+impl fmt::Display for CFStringPathError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return write!(f, "path {:?} cannot be represented as a CFString", self.path);
+  }
+}
+
+impl Error for CFStringPathError {
+  fn description(&self) -> &str {
+    return "path cannot be represented as a CFString";
+  }
+}
+
+impl<'a> TryFrom<&'a OsStr> for CFString {
+  type Error = CFStringPathError;
+
+  fn try_from(path: &'a OsStr) -> Result<CFString, CFStringPathError> {
+    let error = || CFStringPathError { path: PathBuf::from(path) };
+    let cstr = CString::new(path.as_bytes()).map_err(|_| error())?;
+
+    return CFStringCreateWithFileSystemRepresentation(kCFAllocatorDefault, &cstr).ok_or_else(error);
+  }
+}
+
+impl<'a> TryFrom<&'a Path> for CFString {
+  type Error = CFStringPathError;
+
+  fn try_from(path: &'a Path) -> Result<CFString, CFStringPathError> {
+    return CFString::try_from(path.as_os_str());
+  }
+}
+
+impl CFString {
+  pub fn join<T: Subtype<CFStringRef>, S: Subtype<CFStringRef>>(strings: &CFArray<T>, separator: &S) -> CFString {
+    return CFStringCreateByCombiningStrings(kCFAllocatorDefault, strings, separator).expect("could not allocate CFString");
+  }
+}
+
+impl<'a> From<&'a str> for CFString {
+  fn from(string: &'a str) -> CFString {
+    return CFString::from_str(string);
+  }
+}
+
+/// Takes over the string's buffer instead of copying it.
+impl From<String> for CFString {
+  fn from(string: String) -> CFString {
+    return CFStringCreateWithBytesNoCopy(kCFAllocatorDefault, string.into_bytes().into_boxed_slice(), CFStringEncoding::kCFStringEncodingUTF8, false).expect("could not allocate CFString");
+  }
+}
+
+pub trait CFStringClass : Subtype<CFStringRef> {
+  fn get_length(&self) -> CFIndex {
+    return CFStringGetLength(self);
+  }
+
+  fn create_substring(&self, range: CFRange) -> Option<CFString> {
+    return CFStringCreateWithSubstring(kCFAllocatorDefault, self, range);
+  }
+
+  fn create_copy(&self) -> CFString {
+    return CFStringCreateCopy(kCFAllocatorDefault, self).expect("could not allocate CFString");
+  }
+
+  fn compare<S: Subtype<CFStringRef>>(&self, other: &S, flags: CFStringCompareFlags) -> Ordering {
+    return CFStringCompare(self, other, flags).into();
+  }
+
+  /// Compares using the collation rules of `locale`, such as placing "ö" after "z" in Swedish.
+  fn compare_localized<S: Subtype<CFStringRef>, L: Subtype<CFLocaleRef>>(&self, other: &S, flags: CFStringCompareFlags, locale: &L) -> Ordering {
+    let range = CFRangeMake(0, self.get_length());
+    return CFStringCompareWithOptionsAndLocale(self, other, range, flags, Some(locale)).into();
+  }
+
+  fn create_mutable_copy(&self) -> CFMutableString {
+    return CFStringCreateMutableCopy(kCFAllocatorDefault, 0, self).expect("could not allocate CFMutableString");
+  }
+
+  /// Converts to a path that converts back to an equal string. The file system representation may decompose
+  /// characters, so the string's own UTF-8 is used when that would not round-trip. Returns `None` if the string
+  /// contains NUL or unpaired surrogates, or is too long for Core Foundation to size its file system representation.
+  fn to_path_buf(&self) -> Option<PathBuf> {
+    let size = CFStringGetMaximumSizeOfFileSystemRepresentation(self);
+    if size < 0 {
+      return None;
+    }
+
+    let mut buffer = vec![0; size as usize];
+
+    if CFStringGetFileSystemRepresentation(self, &mut buffer) == Boolean::TRUE {
+      let length = buffer.iter().position(|&byte| byte == 0).unwrap_or(buffer.len());
+      buffer.truncate(length);
+    } else {
+      buffer.clear();
+    }
+
+    let round_trips = |path: &Path| CFString::try_from(path).map(|string| self.compare(&string, CFStringCompareFlags::empty()) == Ordering::Equal).unwrap_or(false);
+
+    let path = PathBuf::from(OsStr::from_bytes(&buffer));
+    if !buffer.is_empty() && round_trips(&path) {
+      return Some(path);
+    }
+
+    let path = PathBuf::from(&*self.to_str());
+    if round_trips(&path) {
+      return Some(path);
+    }
+
+    return None;
+  }
+
+  /// Splits on every occurrence of `separator`, so adjacent separators produce empty strings.
+  fn split<S: Subtype<CFStringRef>>(&self, separator: &S) -> CFArray<CFStringRef> {
+    return CFStringCreateArrayBySeparatingStrings(kCFAllocatorDefault, self, separator).expect("could not allocate CFArray");
+  }
+
+  /// Parses a leading integer like `CFStringGetIntValue`, ignoring leading whitespace and anything after the number.
+  /// Returns `None` where Core Foundation would return 0 for a missing number or saturate one that does not fit.
+  fn int_value(&self) -> Option<i32> {
+    let value = CFStringGetIntValue(self);
+
+    if value == 0 || value == i32::min_value() || value == i32::max_value() {
+      return leading_number(&self.to_str(), false).and_then(|number| number.parse().ok());
+    }
+
+    return Some(value);
+  }
+
+  /// Parses a leading floating point number like `CFStringGetDoubleValue`, ignoring leading whitespace and anything
+  /// after the number. Returns `None` if there is no number or it overflows.
+  fn double_value(&self) -> Option<f64> {
+    let value = CFStringGetDoubleValue(self);
+
+    if value == 0.0 {
+      return leading_number(&self.to_str(), true).map(|_| value);
+    }
+
+    return if value.is_finite() { Some(value) } else { None };
+  }
+
+  fn has_prefix<S: Subtype<CFStringRef>>(&self, prefix: &S) -> bool {
+    return CFStringHasPrefix(self, prefix) == Boolean::TRUE;
+  }
+
+  fn has_suffix<S: Subtype<CFStringRef>>(&self, suffix: &S) -> bool {
+    return CFStringHasSuffix(self, suffix) == Boolean::TRUE;
+  }
+
+  /// Finds the first occurrence of `needle`, or the last one with `kCFCompareBackwards`.
+  fn find<S: Subtype<CFStringRef>>(&self, needle: &S, flags: CFStringCompareFlags) -> Option<CFRange> {
+    return CFStringFind(self, needle, flags);
+  }
+
+  fn find_localized<S: Subtype<CFStringRef>, L: Subtype<CFLocaleRef>>(&self, needle: &S, flags: CFStringCompareFlags, locale: &L) -> Option<CFRange> {
+    let range = CFRangeMake(0, self.get_length());
+    return CFStringFindWithOptionsAndLocale(self, needle, range, flags, Some(locale));
+  }
+
+  fn find_all<S: Subtype<CFStringRef>>(&self, needle: &S, flags: CFStringCompareFlags) -> CFStringFindResults {
+    let range = CFRangeMake(0, self.get_length());
+    return CFStringFindResults { results: CFStringCreateArrayWithFindResults(kCFAllocatorDefault, self, needle, range, flags), index: 0 };
+  }
+
+  fn find_character_from_set<C: Subtype<CFCharacterSetRef>>(&self, set: &C, flags: CFStringCompareFlags) -> Option<CFRange> {
+    let range = CFRangeMake(0, self.get_length());
+    return CFStringFindCharacterFromSet(self, set, range, flags);
+  }
+
+  /// Iterates the contents of each line, excluding terminators. Line separators and paragraph separators both end a line.
+  fn lines(&self) -> CFStringSegments<Self> where Self: Sized {
+    return CFStringSegments { string: self, segmentation: Segmentation::Lines, location: 0 };
+  }
+
+  fn paragraphs(&self) -> CFStringSegments<Self> where Self: Sized {
+    return CFStringSegments { string: self, segmentation: Segmentation::Paragraphs, location: 0 };
+  }
+
+  /// Iterates grapheme clusters, such as a base letter with its combining marks or a surrogate pair.
+  fn composed_characters(&self) -> CFStringSegments<Self> where Self: Sized {
+    return CFStringSegments { string: self, segmentation: Segmentation::ComposedCharacters, location: 0 };
+  }
+
+  fn hyphenation_location_before<L: Subtype<CFLocaleRef>>(&self, index: CFIndex, locale: &L) -> Option<(CFIndex, char)> {
+    let range = CFRangeMake(0, self.get_length());
+    return CFStringGetHyphenationLocationBeforeIndex(self, index, range, locale).and_then(|(index, c)| char::from_u32(c).map(|c| (index, c)));
+  }
+
+  /// Returns `None` if the string does not fit in 255 bytes or cannot be represented in `encoding`.
+  fn to_pascal_string(&self, encoding: CFStringEncoding) -> Option<PascalString> {
+    // Copied right away, so nothing can change the string in the meantime.
+    if let Some(string) = unsafe { CFStringGetPascalStringPtr(self, encoding) } {
+      return Some(PascalString::from(string));
+    }
+
+    let mut buffer = [0; 256];
+
+    if CFStringGetPascalString(self, &mut buffer, encoding) == Boolean::FALSE {
+      return None;
+    }
+
+    return Some(PascalString::from_str255(buffer));
+  }
+
+  /// Borrows the contents when the string is immutable and already stored as UTF-8, and otherwise converts them.
+  /// Unpaired surrogates cannot be represented in a Rust string and are replaced with U+FFFD.
+  fn to_str<'a>(&'a self) -> Cow<'a, str> {
+    let utf8 = CFStringEncoding::kCFStringEncodingUTF8;
+    let length = self.get_length();
+
+    if is_immutable(self) {
+      // The C string stops at the first NUL, so it is only the whole string if no UTF-16 units are missing.
+      if let Some(string) = unsafe { CFStringGetCStringPtr(self, utf8) }.and_then(|cstr| str::from_utf8(cstr.to_bytes()).ok()) {
+        if string.chars().map(char::len_utf16).sum::<usize>() == length as usize {
+          return Cow::Borrowed(string);
+        }
+      }
+    }
+
+    if let Some(chars) = unsafe { CFStringGetCharactersPtr(self) } {
+      return Cow::Owned(String::from_utf16_lossy(chars));
+    }
+
+    let range = CFRangeMake(0, length);
+    let (converted, size) = CFStringGetBytes(self, range, utf8, 0, false, None);
+
+    if converted == length {
+      let mut buffer = vec![0; size as usize];
+      CFStringGetBytes(self, range, utf8, 0, false, Some(&mut buffer));
+
+      return Cow::Owned(String::from_utf8(buffer).unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned()));
+    }
+
+    let mut chars = vec![0; length as usize];
+    CFStringGetCharacters(self, range, &mut chars);
+
+    return Cow::Owned(String::from_utf16_lossy(&chars));
+  }
+}
+
+impl<T> CFStringClass for T where T: Subtype<CFStringRef> { }
+
+impl<T> fmt::Display for CFType<T> where T: Subtype<CFTypeRef> + Subtype<CFStringRef> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return f.write_str(&self.to_str());
+  }
+}
+
+impl<'a, T> fmt::Display for CFRef<'a, T> where T: Subtype<CFTypeRef> + Subtype<CFStringRef> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return f.write_str(&self.to_str());
+  }
+}
+
+pub trait CFMutableStringClass : Subtype<CFMutableStringRef> + Subtype<CFStringRef> + Sized {
+  fn append<S: Subtype<CFStringRef>>(&mut self, string: &S) {
+    CFStringAppend(self, string);
+  }
+
+  fn append_str(&mut self, string: &str) {
+    CFStringAppendCharacters(self, &string.encode_utf16().collect::<Vec<_>>());
+  }
+
+  fn append_pascal_str(&mut self, string: &PascalStr, encoding: CFStringEncoding) {
+    CFStringAppendPascalString(self, string, encoding);
+  }
+
+  fn insert<S: Subtype<CFStringRef>>(&mut self, index: CFIndex, string: &S) {
+    CFStringInsert(self, index, string);
+  }
+
+  fn delete(&mut self, range: CFRange) {
+    CFStringDelete(self, range);
+  }
+
+  fn replace<S: Subtype<CFStringRef>>(&mut self, range: CFRange, replacement: &S) {
+    CFStringReplace(self, range, replacement);
+  }
+
+  fn replace_all<S: Subtype<CFStringRef>>(&mut self, replacement: &S) {
+    CFStringReplaceAll(self, replacement);
+  }
+
+  fn find_and_replace<S1: Subtype<CFStringRef>, S2: Subtype<CFStringRef>>(&mut self, needle: &S1, replacement: &S2, flags: CFStringCompareFlags) -> CFIndex {
+    let range = CFRangeMake(0, CFStringGetLength(self));
+    return CFStringFindAndReplace(self, needle, replacement, range, flags);
+  }
+
+  fn pad<S: Subtype<CFStringRef>>(&mut self, pad: &S, length: CFIndex, index_into_pad: CFIndex) {
+    CFStringPad(self, pad, length, index_into_pad);
+  }
+
+  /// Removes leading and trailing repetitions of `trim`.
+  fn trim<S: Subtype<CFStringRef>>(&mut self, trim: &S) {
+    CFStringTrim(self, trim);
+  }
+
+  fn trim_whitespace(&mut self) {
+    CFStringTrimWhitespace(self);
+  }
+
+  /// Maps to lowercase using the rules of `locale`. Pass `CFLocaleGetSystem()` for locale-independent mapping.
+  fn lowercase<L: Subtype<CFLocaleRef>>(&mut self, locale: &L) {
+    CFStringLowercase(self, Some(locale));
+  }
+
+  fn uppercase<L: Subtype<CFLocaleRef>>(&mut self, locale: &L) {
+    CFStringUppercase(self, Some(locale));
+  }
+
+  fn capitalize<L: Subtype<CFLocaleRef>>(&mut self, locale: &L) {
+    CFStringCapitalize(self, Some(locale));
+  }
+
+  fn normalize(&mut self, form: CFStringNormalizationForm) {
+    CFStringNormalize(self, form);
+  }
+
+  /// Removes the distinctions selected by `flags`, such as case or diacritics, for use as a search key.
+  fn fold<L: Subtype<CFLocaleRef>>(&mut self, flags: CFStringCompareFlags, locale: &L) {
+    CFStringFold(self, flags, Some(locale));
+  }
+
+  fn transform(&mut self, transform: Transform, reverse: bool) -> Result<(), CFStringTransformError> {
+    if CFStringTransform(self, None, &transform.identifier(), reverse) == Boolean::FALSE {
+      return Err(CFStringTransformError { transform: transform, reverse: reverse });
+    }
+
+    return Ok(());
+  }
+
+  /// Transforms only `range`, returning the range now occupied by the transformed text.
+  fn transform_range(&mut self, range: CFRange, transform: Transform, reverse: bool) -> Result<CFRange, CFStringTransformError> {
+    let mut range = range;
+
+    if CFStringTransform(self, Some(&mut range), &transform.identifier(), reverse) == Boolean::FALSE {
+      return Err(CFStringTransformError { transform: transform, reverse: reverse });
+    }
+
+    return Ok(range);
+  }
+}
+
+impl<T> CFMutableStringClass for T where T: Subtype<CFMutableStringRef> + Subtype<CFStringRef> { }
+
+impl<T> fmt::Write for CFType<T> where T: Subtype<CFTypeRef> + Subtype<CFMutableStringRef> + Subtype<CFStringRef> {
+  fn write_str(&mut self, string: &str) -> fmt::Result {
+    self.append_str(string);
+    return Ok(());
+  }
+}
+
+impl<T> Extend<char> for CFType<T> where T: Subtype<CFTypeRef> + Subtype<CFMutableStringRef> + Subtype<CFStringRef> {
+  fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+    let mut chars = Vec::new();
+    let mut buffer = [0; 2];
+
+    for c in iter {
+      chars.extend_from_slice(c.encode_utf16(&mut buffer));
+    }
+
+    CFStringAppendCharacters(self, &chars);
+  }
+}
+
+impl<'a, T> Extend<&'a str> for CFType<T> where T: Subtype<CFTypeRef> + Subtype<CFMutableStringRef> + Subtype<CFStringRef> {
+  fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+    for string in iter {
+      self.append_str(string);
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Segmentation {
+  Lines,
+  Paragraphs,
+  ComposedCharacters
+}
+
+/// Iterator over the ranges of the lines, paragraphs or composed characters of a string.
+pub struct CFStringSegments<'a, S: 'a> {
+  string: &'a S,
+  segmentation: Segmentation,
+  location: CFIndex
+}
+
+impl<'a, S: Subtype<CFStringRef>> CFStringSegments<'a, S> {
+  /// Yields each segment as a new string instead of as a range.
+  pub fn substrings(self) -> CFStringSubstrings<'a, S> {
+    return CFStringSubstrings { segments: self };
+  }
+}
+
+impl<'a, S: Subtype<CFStringRef>> Iterator for CFStringSegments<'a, S> {
+  type Item = CFRange;
+
+  fn next(&mut self) -> Option<CFRange> {
+    if self.location >= CFStringGetLength(self.string) {
+      return None;
+    }
+
+    let start = CFRangeMake(self.location, 0);
+    let (segment, end) = match self.segmentation {
+      Segmentation::Lines => {
+        let (begin, end, contentsEnd) = CFStringGetLineBounds(self.string, start);
+        (CFRangeMake(begin, contentsEnd - begin), end)
+      },
+      Segmentation::Paragraphs => {
+        let (begin, end, contentsEnd) = CFStringGetParagraphBounds(self.string, start);
+        (CFRangeMake(begin, contentsEnd - begin), end)
+      },
+      Segmentation::ComposedCharacters => {
+        let range = CFStringGetRangeOfComposedCharactersAtIndex(self.string, self.location);
+        (range, range.location + range.length)
+      }
+    };
+
+    self.location = end;
+
+    return Some(segment);
+  }
+}
+
+pub struct CFStringSubstrings<'a, S: 'a> {
+  segments: CFStringSegments<'a, S>
+}
+
+impl<'a, S: Subtype<CFStringRef>> Iterator for CFStringSubstrings<'a, S> {
+  type Item = CFString;
+
+  fn next(&mut self) -> Option<CFString> {
+    let range = self.segments.next()?;
+    return Some(CFStringCreateWithSubstring(kCFAllocatorDefault, self.segments.string, range).expect("could not allocate CFString"));
+  }
+}
+
+/// A transliteration understood by `CFStringTransform`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transform {
+  StripCombiningMarks,
+  ToLatin,
+  FullwidthHalfwidth,
+  LatinKatakana,
+  LatinHiragana,
+  HiraganaKatakana,
+  MandarinLatin,
+  LatinHangul,
+  LatinArabic,
+  LatinHebrew,
+  LatinThai,
+  LatinCyrillic,
+  LatinGreek,
+  ToXMLHex,
+  ToUnicodeName,
+  StripDiacritics,
+  /// Any ICU transform identifier, such as `"Any-Latin; Latin-ASCII"`.
+  Custom(String)
+}
+
+impl Transform {
+  pub fn identifier(&self) -> CFString {
+    return match *self {
+      Transform::StripCombiningMarks => kCFStringTransformStripCombiningMarks.to_owned(),
+      Transform::ToLatin => kCFStringTransformToLatin.to_owned(),
+      Transform::FullwidthHalfwidth => kCFStringTransformFullwidthHalfwidth.to_owned(),
+      Transform::LatinKatakana => kCFStringTransformLatinKatakana.to_owned(),
+      Transform::LatinHiragana => kCFStringTransformLatinHiragana.to_owned(),
+      Transform::HiraganaKatakana => kCFStringTransformHiraganaKatakana.to_owned(),
+      Transform::MandarinLatin => kCFStringTransformMandarinLatin.to_owned(),
+      Transform::LatinHangul => kCFStringTransformLatinHangul.to_owned(),
+      Transform::LatinArabic => kCFStringTransformLatinArabic.to_owned(),
+      Transform::LatinHebrew => kCFStringTransformLatinHebrew.to_owned(),
+      Transform::LatinThai => kCFStringTransformLatinThai.to_owned(),
+      Transform::LatinCyrillic => kCFStringTransformLatinCyrillic.to_owned(),
+      Transform::LatinGreek => kCFStringTransformLatinGreek.to_owned(),
+      Transform::ToXMLHex => kCFStringTransformToXMLHex.to_owned(),
+      Transform::ToUnicodeName => kCFStringTransformToUnicodeName.to_owned(),
+      Transform::StripDiacritics => kCFStringTransformStripDiacritics.to_owned(),
+      Transform::Custom(ref identifier) => CFString::from_str(identifier)
+    };
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CFStringTransformError {
+  pub transform: Transform,
+  pub reverse: bool
+}
+
+impl fmt::Display for CFStringTransformError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return write!(f, "could not apply {}transform {:?}", if self.reverse { "reverse " } else { "" }, self.transform);
+  }
+}
+
+impl Error for CFStringTransformError {
+  fn description(&self) -> &str {
+    return "could not apply string transform";
+  }
+}
+
+/// Returns a `&'static CFRef<'static, CFStringRef>` for a string literal, like `CFSTR` in C. The string is
+/// created the first time each call site runs and is never released.
+#[macro_export]
+macro_rules! cfstr {
+  ($string:expr) => {{
+    static STRING: ::std::sync::atomic::AtomicPtr<$crate::CFRef<'static, $crate::CFStringRef>> = ::std::sync::atomic::AtomicPtr::new(0 as *mut _);
+    $crate::__cfstr(&STRING, $string)
+  }}
+}
+
+#[doc(hidden)]
+pub fn __cfstr(cache: &'static AtomicPtr<CFRef<'static, CFStringRef>>, string: &'static str) -> &'static CFRef<'static, CFStringRef> {
+  let cached = cache.load(atomic::Ordering::Acquire);
+
+  if !cached.is_null() {
+    return unsafe { &*cached };
+  }
+
+  // Constant strings are interned and immortal, but non-ASCII bytes would be read as Mac Roman.
+  let constant = string.is_ascii() && !string.contains('\0');
+  let cf = if constant {
+    let cstr = CString::new(string).unwrap();
+    unsafe { CFRef::from_get_rule(ext::__CFStringMakeConstantString(cstr.as_ptr())) }
+  } else {
+    unsafe { CFRef::from_get_rule(CFString::from_str(string).into_raw()) }
+  };
+
+  let created = Box::into_raw(Box::new(cf));
+
+  return match cache.compare_exchange(ptr::null_mut(), created, atomic::Ordering::AcqRel, atomic::Ordering::Acquire) {
+    Ok(_) => unsafe { &*created },
+    Err(existing) => {
+      // Another thread filled the cache first, so this thread's copy is never handed out.
+      let lost = unsafe { Box::from_raw(created) };
+
+      if !constant {
+        let cf: CFStringRef = unsafe { lost.upcast() };
+        unsafe { CFRelease(cf) };
+      }
+
+      unsafe { &*existing }
+    }
+  };
+}
+
+/// Formats like `format!` into a new `CFString`. Arguments prefixed with `@` are Core Foundation objects
+/// and are rendered as the `%@` specifier would, which falls back to `CFCopyDescription` for objects
+/// that are not strings. A leading `options = <dictionary or locale>;` is passed to Core Foundation
+/// when rendering those objects and only affects them: every other argument goes through Rust's
+/// formatting as is, so numbers are never localized.
+///
+/// ```ignore
+/// let greeting = cf_format!("{} has {} items", @name, count);
+/// let localized = cf_format!(options = CFLocaleCopyCurrent(); "{} on {}", @name, @date);
+/// ```
+#[macro_export]
+macro_rules! cf_format {
+  (@munch $options:ident, $format:expr, [$($args:tt)*]) => {
+    $crate::CFString::from_str(&format!($format, $($args)*))
+  };
+  (@munch $options:ident, $format:expr, [$($args:tt)*] @ $arg:expr) => {
+    $crate::cf_format!(@munch $options, $format, [$($args)* $arg.__cf_format_arg(&$options),])
+  };
+  (@munch $options:ident, $format:expr, [$($args:tt)*] @ $arg:expr, $($rest:tt)*) => {
+    $crate::cf_format!(@munch $options, $format, [$($args)* $arg.__cf_format_arg(&$options),] $($rest)*)
+  };
+  (@munch $options:ident, $format:expr, [$($args:tt)*] $arg:expr) => {
+    $crate::cf_format!(@munch $options, $format, [$($args)* $arg,])
+  };
+  (@munch $options:ident, $format:expr, [$($args:tt)*] $arg:expr, $($rest:tt)*) => {
+    $crate::cf_format!(@munch $options, $format, [$($args)* $arg,] $($rest)*)
+  };
+  (options = $options:expr; $format:expr) => {
+    $crate::cf_format!(options = $options; $format,)
+  };
+  (options = $options:expr; $format:expr, $($rest:tt)*) => {{
+    #[allow(unused_imports)] use $crate::__CFFormatValue;
+    let options = &$options;
+    #[allow(unused_variables)] let options = Some($crate::__cf_format_options(options));
+    $crate::cf_format!(@munch options, $format, [] $($rest)*)
+  }};
+  ($format:expr) => {
+    $crate::cf_format!($format,)
+  };
+  ($format:expr, $($rest:tt)*) => {{
+    #[allow(unused_imports)] use $crate::__CFFormatValue;
+    #[allow(unused_variables)] let options: Option<$crate::CFTypeRef> = None;
+    $crate::cf_format!(@munch options, $format, [] $($rest)*)
+  }};
+}
+
+#[doc(hidden)]
+pub fn __cf_format_options<O: Subtype<CFTypeRef>>(options: &O) -> CFTypeRef {
+  return unsafe { options.upcast() };
+}
+
+#[doc(hidden)]
+pub trait __CFFormatValue : Subtype<CFTypeRef> {
+  fn __cf_format_arg<'a>(&'a self, options: &'a Option<CFTypeRef>) -> __CFFormatArg<'a> {
+    return __CFFormatArg { value: unsafe { self.upcast() }, options: options, lifetime: PhantomData };
+  }
+}
+
+impl<T> __CFFormatValue for T where T: Subtype<CFTypeRef> { }
+
+#[doc(hidden)]
+pub struct __CFFormatArg<'a> {
+  value: CFTypeRef,
+  options: &'a Option<CFTypeRef>,
+  lifetime: PhantomData<&'a CFTypeRef>
+}
+
+impl<'a> fmt::Display for __CFFormatArg<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let options = self.options.as_ref().map_or(CFTypeRef(0), |options| CFTypeRef(options.0));
+    let string: CFString = unsafe {
+      CFType::try_from_create_rule(ext::CFStringCreateWithFormat(kCFAllocatorDefault.upcast(), options, cfstr!("%@").upcast(), self.value.0 as *const c_void))
+    }.expect("could not allocate CFString");
+
+    return f.pad(&string.to_str());
+  }
+}
+
+/// Iterator over the ranges of every match, as returned by `CFStringClass::find_all`.
+pub struct CFStringFindResults {
+  results: Option<CFType<CFArrayRef>>,
+  index: CFIndex
+}
+
+impl Iterator for CFStringFindResults {
+  type Item = CFRange;
+
+  fn next(&mut self) -> Option<CFRange> {
+    let results = self.results.as_ref()?;
+
+    if self.index >= results.get_count() {
+      return None;
+    }
+
+    let range = unsafe { *(CFArrayGetValueAtIndex(results, self.index) as *const CFRange) };
+    self.index += 1;
+
+    return Some(range);
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = self.results.as_ref().map_or(0, |results| (results.get_count() - self.index) as usize);
+    return (remaining, Some(remaining));
+  }
+}
+
+impl ExactSizeIterator for CFStringFindResults { }
+
+macro_rules! ordered_by {
+  ($(#[$attr:meta])* $name:ident, $flags:expr) => {
+    $(#[$attr])*
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct $name<T>(pub T);
+
+    impl<T> ops::Deref for $name<T> {
+      type Target = T;
+
+      fn deref(&self) -> &T {
+        return &self.0;
+      }
+    }
+
+    impl<T: Subtype<CFStringRef>> PartialEq for $name<T> {
+      fn eq(&self, other: &$name<T>) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+      }
+    }
+
+    impl<T: Subtype<CFStringRef>> Eq for $name<T> { }
+
+    impl<T: Subtype<CFStringRef>> PartialOrd for $name<T> {
+      fn partial_cmp(&self, other: &$name<T>) -> Option<Ordering> {
+        return Some(self.cmp(other));
+      }
+    }
+
+    impl<T: Subtype<CFStringRef>> Ord for $name<T> {
+      fn cmp(&self, other: &$name<T>) -> Ordering {
+        return self.0.compare(&other.0, $flags);
+      }
+    }
+  }
+}
+
+ordered_by! {
+  /// Orders the wrapped string ignoring case, so that "Apple" and "apple" are the same key.
+  CaseInsensitive, CFStringCompareFlags::kCFCompareCaseInsensitive
+}
+
+ordered_by! {
+  /// Orders the wrapped string by the value of embedded numbers, so that "file2" sorts before "file10".
+  Numeric, CFStringCompareFlags::kCFCompareNumerically
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::*;
+  use std::collections::BTreeMap;
+  use std::path::{Path, PathBuf};
+
+  #[test]
+  fn it_creates() {
+    assert_eq!(CFString::from_str("hagane").get_length(), 6);
+    assert_eq!(CFString::from("鋼").get_length(), 1);
+    assert_eq!(CFString::from_utf16(&[0xD83D, 0xDE00]).get_length(), 2);
+
+    let latin1 = CFString::from_bytes(&[0x68, 0xE9], CFStringEncoding::kCFStringEncodingISOLatin1).unwrap();
+    assert!(latin1.equal(&CFString::from_str("hé")));
+  }
+
+  #[test]
+  fn it_rejects_invalid_bytes() {
+    let error = CFString::from_bytes(&[0xFF, 0xFE, 0xFD], CFStringEncoding::kCFStringEncodingUTF8).err();
+    assert_eq!(error, Some(CFStringEncodingError { encoding: CFStringEncoding::kCFStringEncodingUTF8 }));
+  }
+
+  #[test]
+  fn it_copies_substrings() {
+    let string = CFString::from_str("hagane");
+
+    assert!(string.create_substring(CFRangeMake(2, 4)).unwrap().equal(&CFString::from_str("gane")));
+    assert!(string.create_substring(CFRangeMake(4, 4)).is_none());
+    assert!(string.create_copy().equal(&string));
+  }
+
+  #[test]
+  fn it_converts_to_rust_strings() {
+    let mutable = CFMutableString::from_str("hagane");
+    let array = CFArray::<CFStringRef>::from_slice(&[mutable]);
+    let element = array.get(0).unwrap();
+    let is_borrowed = |string: Cow<str>| match string { Cow::Borrowed(_) => true, Cow::Owned(_) => false };
+    assert!(!is_borrowed(element.to_str()));
+    assert!(is_borrowed(CFString::from_str("hagane").to_str()));
+
+    assert_eq!(CFString::from_str("hagane").to_str(), "hagane");
+    assert_eq!(CFString::from_str("héllo, 鋼").to_string(), "héllo, 鋼");
+    assert_eq!(format!("<{}>", CFString::from_utf16(&[0xD83D, 0xDE00])), "<😀>");
+    assert_eq!(CFString::from_utf16(&[0x61, 0xD800]).to_string(), "a\u{FFFD}");
+    assert_eq!(CFString::from_str("a\0b").to_str(), "a\0b");
+    assert_eq!(CFString::from_str("\0").to_string(), "\0");
+  }
+
+  #[test]
+  fn it_converts_encodings() {
+    let utf8 = CFStringEncoding::kCFStringEncodingUTF8;
+
+    assert_eq!(CFStringEncoding::from_iana_charset_name("utf-8"), Some(utf8));
+    assert_eq!(CFStringEncoding::from_iana_charset_name("not-a-charset"), None);
+    assert_eq!(utf8.iana_charset_name().unwrap().to_string(), "UTF-8");
+
+    assert_eq!(CFStringEncoding::kCFStringEncodingWindowsLatin1.windows_codepage(), Some(1252));
+    assert_eq!(CFStringEncoding::from_windows_codepage(1252), Some(CFStringEncoding::kCFStringEncodingWindowsLatin1));
+
+    assert!(CFStringEncoding::available().contains(&utf8));
+    assert!(utf8.is_available());
+
+    assert_eq!(format!("{:?}", utf8), "kCFStringEncodingUTF8");
+    assert_eq!(format!("{:?}", CFStringEncoding(0x1234)), "CFStringEncoding(0x1234)");
+  }
+
+  #[test]
+  fn it_compares() {
+    let flags = CFStringCompareFlags::kCFCompareCaseInsensitive | CFStringCompareFlags::kCFCompareNumerically;
+    assert!(flags.contains(CFStringCompareFlags::kCFCompareNumerically));
+    assert!(!flags.contains(CFStringCompareFlags::kCFCompareBackwards));
+    assert_eq!(format!("{:?}", flags), "kCFCompareCaseInsensitive | kCFCompareNumerically");
+    assert_eq!(format!("{:?}", CFStringCompareFlags(2)), "0x2");
+
+    let apple = CFString::from_str("apple");
+    let upper = CFString::from_str("APPLE");
+    assert_eq!(apple.compare(&upper, CFStringCompareFlags::empty()), Ordering::Greater);
+    assert_eq!(apple.compare(&upper, CFStringCompareFlags::kCFCompareCaseInsensitive), Ordering::Equal);
+
+    let (o, z) = (CFString::from_str("ö"), CFString::from_str("z"));
+    assert_eq!(o.compare_localized(&z, CFStringCompareFlags::empty(), &CFLocale::from_identifier("en_US")), Ordering::Less);
+    assert_eq!(o.compare_localized(&z, CFStringCompareFlags::empty(), &CFLocale::from_identifier("sv_SE")), Ordering::Greater);
+  }
+
+  #[test]
+  fn it_searches() {
+    let string = CFString::from_str("Hagane hagane HAGANE");
+    let needle = CFString::from_str("hagane");
+
+    assert_eq!(string.find(&needle, CFStringCompareFlags::empty()), Some(CFRangeMake(7, 6)));
+    assert_eq!(string.find(&needle, CFStringCompareFlags::kCFCompareCaseInsensitive | CFStringCompareFlags::kCFCompareBackwards), Some(CFRangeMake(14, 6)));
+    assert_eq!(string.find(&CFString::from_str("steel"), CFStringCompareFlags::empty()), None);
+
+    let matches: Vec<ops::Range<usize>> = string.find_all(&needle, CFStringCompareFlags::kCFCompareCaseInsensitive).map(|range| ops::Range::try_from(range).unwrap()).collect();
+    assert_eq!(matches, [0..6, 7..13, 14..20]);
+    assert_eq!(string.find_all(&CFString::from_str("steel"), CFStringCompareFlags::empty()).count(), 0);
+
+    assert!(string.has_prefix(&CFString::from_str("Hag")));
+    assert!(string.has_suffix(&CFString::from_str("NE")));
+    assert!(!string.has_suffix(&needle));
+
+    let whitespace = CFCharacterSet::predefined(CFCharacterSetPredefinedSet::kCFCharacterSetWhitespace);
+    assert_eq!(string.find_character_from_set(&whitespace, CFStringCompareFlags::empty()), Some(CFRange::try_from(6..7).unwrap()));
+  }
+
+  #[test]
+  fn it_mutates() {
+    let mut string = CFMutableString::from_str("  hagane ");
+    string.trim_whitespace();
+    string.append(&CFString::from_str("-steel"));
+    string.insert(0, &CFString::from_str("鋼 "));
+    assert_eq!(string.to_string(), "鋼 hagane-steel");
+
+    string.replace(CFRange::try_from(2..8).unwrap(), &CFString::from_str("HAGANE"));
+    string.delete(CFRangeMake(0, 2));
+    assert_eq!(string.to_string(), "HAGANE-steel");
+
+    assert_eq!(string.find_and_replace(&CFString::from_str("e"), &CFString::from_str("3"), CFStringCompareFlags::kCFCompareCaseInsensitive), 3);
+    assert_eq!(string.to_string(), "HAGAN3-st33l");
+
+    string.pad(&CFString::from_str(".:"), 15, 1);
+    assert_eq!(string.to_string(), "HAGAN3-st33l:.:");
+    string.pad(&CFString::from_str("."), 6, 0);
+    string.trim(&CFString::from_str("H"));
+    assert_eq!(string.to_string(), "AGAN3");
+
+    string.replace_all(&CFString::from_str("reset"));
+    assert!(string.equal(&CFString::from_str("reset")));
+    assert_eq!(CFString::from_str("copy").create_mutable_copy().to_string(), "copy");
+  }
+
+  #[test]
+  fn it_copies_mutable_strings_on_clone() {
+    let mut string = CFMutableString::from_str("hagane");
+    let copy = string.clone();
+    let immutable = string.clone().downcast::<CFStringRef>().ok().unwrap();
+    let borrowed = copy.to_str();
+
+    string.append_str(" steel");
+
+    assert_eq!(string.to_string(), "hagane steel");
+    assert_eq!(borrowed, "hagane");
+    assert_eq!(immutable.to_string(), "hagane");
+  }
+
+  #[test]
+  fn it_normalizes() {
+    let mut string = CFMutableString::from_str("\u{E9}\u{FB01}");
+    string.normalize(CFStringNormalizationForm::kCFStringNormalizationFormD);
+    assert_eq!(string.to_string(), "e\u{301}\u{FB01}");
+    string.normalize(CFStringNormalizationForm::kCFStringNormalizationFormKC);
+    assert_eq!(string.to_string(), "\u{E9}fi");
+
+    let system = CFLocaleGetSystem();
+    let mut key = CFMutableString::from_str("Ärger ＡＢＣ");
+    key.fold(CFStringCompareFlags::kCFCompareCaseInsensitive | CFStringCompareFlags::kCFCompareDiacriticInsensitive | CFStringCompareFlags::kCFCompareWidthInsensitive, &system);
+    assert_eq!(key.to_string(), "arger abc");
+  }
+
+  #[test]
+  fn it_maps_case() {
+    let system = CFLocaleGetSystem();
+    let mut string = CFMutableString::from_str("hello wORLD");
+    string.capitalize(&system);
+    assert_eq!(string.to_string(), "Hello World");
+    string.uppercase(&system);
+    assert_eq!(string.to_string(), "HELLO WORLD");
+    string.lowercase(&system);
+    assert_eq!(string.to_string(), "hello world");
+
+    let mut turkish = CFMutableString::from_str("i");
+    turkish.uppercase(&CFLocale::from_identifier("tr_TR"));
+    assert_eq!(turkish.to_string(), "\u{130}");
+  }
+
+  #[test]
+  fn it_transforms() {
+    let mut string = CFMutableString::from_str("ひらがな");
+    string.transform(Transform::HiraganaKatakana, false).unwrap();
+    assert_eq!(string.to_string(), "ヒラガナ");
+    string.transform(Transform::HiraganaKatakana, true).unwrap();
+    assert_eq!(string.to_string(), "ひらがな");
+
+    let mut slug = CFMutableString::from_str("Привет, Café");
+    slug.transform(Transform::Custom("Any-Latin; Latin-ASCII".to_string()), false).unwrap();
+    assert_eq!(slug.to_string(), "Privet, Cafe");
+
+    let mut partial = CFMutableString::from_str("Café Café");
+    assert_eq!(partial.transform_range(CFRangeMake(5, 4), Transform::StripDiacritics, false), Ok(CFRangeMake(5, 4)));
+    assert_eq!(partial.to_string(), "Café Cafe");
+
+    let error = partial.transform(Transform::Custom("Not-A-Transform".to_string()), true).unwrap_err();
+    assert_eq!(error, CFStringTransformError { transform: Transform::Custom("Not-A-Transform".to_string()), reverse: true });
+    assert_eq!(Transform::ToLatin.identifier().to_string(), "Any-Latin");
+  }
+
+  #[test]
+  fn it_writes() {
+    use std::fmt::Write;
+
+    let mut string = CFMutableString::new();
+    write!(string, "{}-{:03}", "hagane", 7).unwrap();
+    string.extend("ö😀".chars());
+    string.extend(vec!["a", "b"]);
+    assert_eq!(string.to_string(), "hagane-007ö😀ab");
+    assert_eq!(string.get_length(), 15);
+  }
+
+  #[test]
+  fn it_segments() {
+    let string = CFString::from_str("one\r\ntwo\u{2028}three\n\nfour");
+
+    let lines: Vec<String> = string.lines().substrings().map(|line| line.to_string()).collect();
+    assert_eq!(lines, ["one", "two", "three", "", "four"]);
+
+    let paragraphs: Vec<String> = string.paragraphs().substrings().map(|paragraph| paragraph.to_string()).collect();
+    assert_eq!(paragraphs, ["one", "two\u{2028}three", "", "four"]);
+
+    assert_eq!(CFStringGetLineBounds(&string, CFRangeMake(1, 0)), (0, 5, 3));
+    assert_eq!(CFString::from_str("").lines().count(), 0);
+
+    let clusters: Vec<CFRange> = CFString::from_str("e\u{301}\u{1F44D}\u{1F3FD}a").composed_characters().collect();
+    assert_eq!(clusters, [CFRangeMake(0, 2), CFRangeMake(2, 4), CFRangeMake(6, 1)]);
+  }
+
+  #[test]
+  fn it_hyphenates() {
+    let locale = CFLocale::from_identifier("en_US");
+    assert!(locale.is_hyphenation_available());
+
+    let string = CFString::from_str("hyphenation");
+    let (index, _) = string.hyphenation_location_before(11, &locale).unwrap();
+    assert!(index > 0 && index < 11);
+
+    assert_eq!(string.hyphenation_location_before(12, &locale), None);
+    assert_eq!(string.hyphenation_location_before(-1, &locale), None);
+    assert_eq!(CFStringGetHyphenationLocationBeforeIndex(&string, 5, CFRangeMake(6, 5), &locale), None);
+    assert_eq!(CFStringGetHyphenationLocationBeforeIndex(&string, 11, CFRangeMake(0, 12), &locale), None);
+  }
+
+  #[test]
+  fn it_splits_and_joins() {
+    let parts = CFString::from_str("a,b,,c").split(&CFString::from_str(","));
+    assert_eq!(parts.iter().map(|part| part.to_string()).collect::<Vec<_>>(), ["a", "b", "", "c"]);
+    assert_eq!(CFString::join(&parts, &CFString::from_str("-")).to_string(), "a-b--c");
+    assert_eq!(CFString::join(&CFArray::<CFStringRef>::from_slice::<CFString>(&[]), &CFString::from_str("-")).to_string(), "");
+  }
+
+  #[test]
+  fn it_parses_numbers() {
+    assert_eq!(CFString::from_str("42").int_value(), Some(42));
+    assert_eq!(CFString::from_str("  -7 apples").int_value(), Some(-7));
+    assert_eq!(CFString::from_str("12abc").int_value(), Some(12));
+    assert_eq!(CFString::from_str("+5").int_value(), Some(5));
+    assert_eq!(CFString::from_str("0").int_value(), Some(0));
+    assert_eq!(CFString::from_str("-00x").int_value(), Some(0));
+    assert_eq!(CFString::from_str("apples").int_value(), None);
+    assert_eq!(CFString::from_str("").int_value(), None);
+    assert_eq!(CFString::from_str(".0").int_value(), None);
+    assert_eq!(CFString::from_str("+-0").int_value(), None);
+    assert_eq!(CFString::from_str("--1").int_value(), None);
+    assert_eq!(CFString::from_str("2147483647").int_value(), Some(i32::max_value()));
+    assert_eq!(CFString::from_str("-2147483648").int_value(), Some(i32::min_value()));
+    assert_eq!(CFString::from_str("2147483648").int_value(), None);
+    assert_eq!(CFString::from_str("-2147483649").int_value(), None);
+
+    assert_eq!(CFString::from_str("3.5").double_value(), Some(3.5));
+    assert_eq!(CFString::from_str("-0.0").double_value(), Some(0.0));
+    assert_eq!(CFString::from_str(".0").double_value(), Some(0.0));
+    assert_eq!(CFString::from_str(" 0.5kg").double_value(), Some(0.5));
+    assert_eq!(CFString::from_str("1e5").double_value(), Some(100000.0));
+    assert_eq!(CFString::from_str("").double_value(), None);
+    assert_eq!(CFString::from_str(".").double_value(), None);
+    assert_eq!(CFString::from_str("+-0.5").double_value(), None);
+    assert_eq!(CFString::from_str("inf").double_value(), None);
+    assert_eq!(CFString::from_str("NaN").double_value(), None);
+    assert_eq!(CFString::from_str("1e999").double_value(), None);
+  }
+
+  #[test]
+  fn it_makes_literals() {
+    let key = cfstr!("hagane");
+    assert_eq!(key.to_string(), "hagane");
+    assert!(key.equal(cfstr!("hagane")));
+    assert_eq!(cfstr!("鋼 steel").to_string(), "鋼 steel");
+    assert_eq!(cfstr!("a\0b").get_length(), 3);
+
+    let addresses: Vec<*const CFRef<CFStringRef>> = (0..3).map(|_| cfstr!("cached") as *const _).collect();
+    assert!(addresses.iter().all(|&address| address == addresses[0]));
+  }
+
+  #[test]
+  fn it_formats() {
+    let name = CFString::from_str("hagane");
+    assert_eq!(cf_format!("{} has {} items", @name, 3).to_string(), "hagane has 3 items");
+    assert_eq!(cf_format!("[{:>8}] 100%", @name).to_string(), "[  hagane] 100%");
+    assert_eq!(cf_format!("{}", @CFString::from_str("%@ %d")).to_string(), "%@ %d");
+    assert_eq!(cf_format!("plain").to_string(), "plain");
+    assert!(cf_format!("{}", @*kCFNull).to_string().starts_with("<CFNull"));
+
+    assert_eq!(cf_format!("{}-{}", @name, @name).to_string(), "hagane-hagane");
+    assert_eq!(cf_format!(options = CFLocale::from_identifier("sv_SE"); "{}: {} {}, {}", 1.5, @name, @name, @name).to_string(), "1.5: hagane hagane, hagane");
+
+    let array = CFArray::<CFStringRef>::from_slice(&[CFString::from_str("steel")]);
+    assert!(cf_format!(options = CFDictionary::from_pairs::<CFString, CFString>(&[]); "{}", @array).to_string().contains("steel"));
+  }
+
+  #[test]
+  fn it_converts_paths() {
+    let composed = Path::new("/tmp/hagane/\u{E9}t\u{E9} \u{92FC}.txt");
+    let string = CFString::try_from(composed).unwrap();
+    assert_eq!(string.to_string(), "/tmp/hagane/\u{E9}t\u{E9} \u{92FC}.txt");
+    assert_eq!(string.to_path_buf().unwrap(), composed);
+
+    let decomposed = CFString::from_str("e\u{301}t\u{E9}");
+    assert!(CFString::try_from(decomposed.to_path_buf().unwrap().as_path()).unwrap().equal(&decomposed));
+
+    assert_eq!(CFString::try_from(Path::new("a\0b")).err(), Some(CFStringPathError { path: PathBuf::from("a\0b") }));
+    assert_eq!(CFString::from_utf16(&[0x61, 0xD800]).to_path_buf(), None);
+  }
+
+  #[test]
+  fn it_borrows_rust_buffers() {
+    let chars: Vec<UniChar> = "h\u{E5}gane".encode_utf16().collect();
+    let ptr = chars.as_ptr();
+    let string = CFString::from_utf16_vec(chars);
+    assert_eq!(unsafe { CFStringGetCharactersPtr(&string) }.map(|chars| chars.as_ptr()), Some(ptr));
+    assert_eq!(string.to_string(), "h\u{E5}gane");
+
+    let bytes = String::from("steel ").repeat(100);
+    assert_eq!(CFString::from(bytes.clone()).to_string(), bytes);
+
+    let mut chars = Vec::with_capacity(8);
+    chars.extend("abc".encode_utf16());
+    let mut mutable = CFMutableString::from_utf16_vec(chars);
+    mutable.append_str("def");
+    mutable.append_str(" and more than eight characters");
+    assert_eq!(mutable.to_string(), "abcdef and more than eight characters");
+  }
+
+  #[test]
+  fn it_converts_pascal_strings() {
+    let mac_roman = CFStringEncoding::kCFStringEncodingMacRoman;
+    let string = CFString::from_pascal_str(PascalStr::from_bytes_with_len(b"\x04caf\x8E").unwrap(), mac_roman).unwrap();
+    assert_eq!(string.to_string(), "caf\u{E9}");
+    assert_eq!(string.to_pascal_string(mac_roman).unwrap().to_bytes(), b"caf\x8E");
+
+    assert_eq!(CFString::from_str("\u{92FC}").to_pascal_string(mac_roman), None);
+    assert_eq!(CFString::from_str(&"a".repeat(256)).to_pascal_string(mac_roman), None);
+
+    let mut mutable = CFMutableString::from_str("r\u{E9}sum");
+    mutable.append_pascal_str(&PascalString::new(b"\x8E").unwrap(), mac_roman);
+    assert_eq!(mutable.to_string(), "r\u{E9}sum\u{E9}");
+  }
+
+  #[test]
+  fn it_orders_keys() {
+    let mut map = BTreeMap::new();
+    map.insert(CaseInsensitive(CFString::from_str("Apple")), 1);
+    map.insert(CaseInsensitive(CFString::from_str("apple")), 2);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map[&CaseInsensitive(CFString::from_str("APPLE"))], 2);
+
+    let mut files: Vec<_> = ["file10", "file2", "file1"].iter().map(|name| Numeric(CFString::from_str(name))).collect();
+    files.sort();
+    assert_eq!(files.iter().map(|file| file.to_string()).collect::<Vec<_>>(), ["file1", "file2", "file10"]);
   }
 }
@@ -1,3 +1,22 @@
 pub unsafe trait Subtype<T> : Sized {
   unsafe fn upcast(&self) -> T;
 }
+
+pub unsafe trait Subclass : Sized {
+  type Parent;
+
+  unsafe fn parent(&self) -> Self::Parent;
+}
+
+unsafe impl<T, U> Subtype<U> for T where T: Subclass, T::Parent: Subtype<U> {
+  unsafe fn upcast(&self) -> U {
+    return self.parent().upcast();
+  }
+}
+
+pub unsafe trait Downcast<T> : Subtype<T> {
+  type TypeID : Eq;
+
+  fn type_id() -> Self::TypeID;
+  unsafe fn downcast_unchecked(object: &T) -> Self;
+}
@@ -0,0 +1,162 @@
+extern crate proc_macro;
+#[macro_use] extern crate quote;
+#[macro_use] extern crate syn;
+
+use proc_macro::TokenStream;
+
+use syn::{Data, DeriveInput, Error, Fields, Ident, Path};
+use syn::parse::{Parse, ParseStream, Parser, Result};
+use syn::punctuated::Punctuated;
+
+struct Argument {
+  name: Ident,
+  value: Path
+}
+
+impl Parse for Argument {
+  fn parse(input: ParseStream) -> Result<Argument> {
+    let name = input.parse()?;
+    input.parse::<Token![=]>()?;
+    let value = input.parse()?;
+
+    return Ok(Argument { name: name, value: value });
+  }
+}
+
+/// Declares a Core Foundation wrapper type.
+///
+/// Applied to a unit struct, this generates the transparent newtype along with `Subtype` impls for
+/// itself and, through `Subclass`, for every ancestor of `parent`. `type_id` names the function
+/// used to check downcasts from `CFTypeRef`, and `owned` declares an alias for the owned `CFType`.
+/// Cloning a `CFType` retains the object unless `clone` names a function that copies it instead,
+/// which mutable types need so that clones do not share their contents.
+///
+/// ```ignore
+/// #[cf_type(parent = CFTypeRef, type_id = CFStringGetTypeID, owned = CFString)]
+/// pub struct CFStringRef;
+/// ```
+#[proc_macro_attribute]
+pub fn cf_type(args: TokenStream, input: TokenStream) -> TokenStream {
+  let item = parse_macro_input!(input as DeriveInput);
+
+  let args = match Punctuated::<Argument, Token![,]>::parse_terminated.parse(args) {
+    Ok(args) => args,
+    Err(error) => return error.to_compile_error().into()
+  };
+
+  return match expand(item, args) {
+    Ok(tokens) => tokens,
+    Err(error) => error.to_compile_error().into()
+  };
+}
+
+fn expand(item: DeriveInput, args: Punctuated<Argument, Token![,]>) -> Result<TokenStream> {
+  let mut parent = None;
+  let mut type_id = None;
+  let mut owned = None;
+  let mut clone = None;
+
+  for arg in args {
+    let slot = match arg.name.to_string().as_str() {
+      "parent" => &mut parent,
+      "type_id" => &mut type_id,
+      "owned" => &mut owned,
+      "clone" => &mut clone,
+      _ => return Err(Error::new(arg.name.span(), "expected `parent`, `type_id`, `owned` or `clone`"))
+    };
+
+    if slot.is_some() {
+      return Err(Error::new(arg.name.span(), "duplicate argument"));
+    }
+
+    *slot = Some(arg.value);
+  }
+
+  match item.data {
+    Data::Struct(ref data) => match data.fields {
+      Fields::Unit => (),
+      _ => return Err(Error::new(item.ident.span(), "expected a unit struct"))
+    },
+    _ => return Err(Error::new(item.ident.span(), "expected a unit struct"))
+  }
+
+  if !item.generics.params.is_empty() {
+    return Err(Error::new(item.ident.span(), "wrapper types cannot be generic"));
+  }
+
+  let parent = match parent {
+    Some(parent) => parent,
+    None => return Err(Error::new(item.ident.span(), "missing `parent` argument"))
+  };
+
+  let attrs = &item.attrs;
+  let vis = &item.vis;
+  let name = &item.ident;
+
+  let downcast = match type_id {
+    Some(type_id) => quote! {
+      unsafe impl ::hagane_core::Downcast<crate::CFTypeRef> for #name {
+        type TypeID = crate::CFTypeID;
+
+        fn type_id() -> crate::CFTypeID {
+          return #type_id();
+        }
+
+        unsafe fn downcast_unchecked(object: &crate::CFTypeRef) -> #name {
+          return #name(object.0);
+        }
+      }
+    },
+    None => quote! { }
+  };
+
+  let owned = match owned {
+    Some(ref owned) if owned.segments.len() != 1 => return Err(Error::new(item.ident.span(), "`owned` must be a plain identifier")),
+    Some(owned) => quote! {
+      #vis type #owned = crate::CFType<#name>;
+    },
+    None => quote! { }
+  };
+
+  let clone = match clone {
+    Some(clone) => quote! {
+      impl Clone for crate::CFType<#name> {
+        fn clone(&self) -> crate::CFType<#name> {
+          return #clone(self);
+        }
+      }
+    },
+    None => quote! {
+      impl Clone for crate::CFType<#name> {
+        fn clone(&self) -> crate::CFType<#name> {
+          return unsafe { crate::CFType::from_get_rule(::hagane_core::Subtype::<#name>::upcast(self)) };
+        }
+      }
+    }
+  };
+
+  let tokens = quote! {
+    #(#attrs)*
+    #[repr(transparent)] #vis struct #name(crate usize);
+
+    unsafe impl ::hagane_core::Subclass for #name {
+      type Parent = #parent;
+
+      unsafe fn parent(&self) -> #parent {
+        return #parent(self.0);
+      }
+    }
+
+    unsafe impl ::hagane_core::Subtype<#name> for #name {
+      unsafe fn upcast(&self) -> #name {
+        return #name(self.0);
+      }
+    }
+
+    #downcast
+    #owned
+    #clone
+  };
+
+  return Ok(tokens.into());
+}